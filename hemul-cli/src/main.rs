@@ -1,6 +1,16 @@
+use std::{cell::RefCell, rc::Rc};
+
 use clap::Parser;
 use clap_stdin::MaybeStdin;
-use hemul::{Tickable, Word, bus::Bus, cpu::Cpu, memory::Memory, oscillator::Oscillator};
+use hemul::{
+    Error, Resettable, RunState, Tickable, Word,
+    bus::Bus,
+    cpu::{Cpu, Mode},
+    memory::{Memory, assemble},
+    oscillator::Oscillator,
+    power::PowerOn,
+};
+use tracing_subscriber::EnvFilter;
 
 /// Hemul VM
 #[derive(Parser, Debug)]
@@ -19,28 +29,91 @@ struct Args {
     #[arg(short, long)]
     #[clap(default_value_t = 1.79)]
     mhz: f64,
+
+    /// Registers and RAM at power on: zeroed, pattern:<byte> or random:<seed>
+    #[arg(short, long)]
+    #[clap(default_value = "zeroed")]
+    power_on: PowerOn,
+
+    /// Execution mode: fast, original or stepped
+    #[arg(long)]
+    #[clap(default_value = "fast")]
+    mode: Mode,
+}
+
+/// Lets the oscillator tick the cpu while main still reads its counters
+struct Shared<T>(Rc<RefCell<T>>);
+
+impl<T: Tickable> Tickable for Shared<T> {
+    fn tick(&mut self) -> Result<(), Error> {
+        self.0.borrow_mut().tick()
+    }
+
+    fn run_state_get(&self) -> RunState {
+        self.0.borrow().run_state_get()
+    }
+}
+
+/// Print why the cpu halted and how long it ran
+fn report(cpu: &Cpu<Bus>, reason: &str) {
+    eprintln!(
+        "cpu {reason} after {} instructions and {} cycles",
+        cpu.instructions_get(),
+        cpu.cycles_get()
+    );
 }
 
 fn main() {
     let args = Args::parse();
 
-    let memory = if args.asm {
-        Memory::from(args.bin.as_str())
+    // Events are filtered with RUST_LOG, like RUST_LOG=hemul::cpu=trace
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let program = if args.asm {
+        assemble(args.bin.as_str())
     } else {
-        Memory::from(args.bin.as_bytes())
+        args.bin.as_bytes().to_vec()
     };
+    let mut memory = Memory::with_power_on(args.power_on);
+    if let Err(e) = memory.load(&program) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
 
     let mut bus = Bus::default();
-    bus.connect("memory", 0, Word::MAX, Box::new(memory));
+    if let Err(e) = bus.connect("memory", 0, Word::MAX, Box::new(memory)) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
 
-    let cpu = Cpu::new(bus);
+    let mut cpu = Cpu::with_mode(bus, args.mode);
+    cpu.power_on(args.power_on);
+    if let Err(e) = cpu.reset() {
+        panic!("{}", e);
+    }
+    let cpu = Rc::new(RefCell::new(cpu));
 
     let mut oscillator = Oscillator::from_megahertz(args.mhz);
-    oscillator.connect("cpu", Box::new(cpu));
+    oscillator.connect("cpu", Box::new(Shared(Rc::clone(&cpu))));
 
     loop {
         if let Err(e) = oscillator.tick() {
             panic!("{}", e);
         }
+
+        match oscillator.run_state_get() {
+            RunState::Running | RunState::Waiting => {}
+            RunState::Stopped => {
+                report(&cpu.borrow(), "stopped");
+                return;
+            }
+            RunState::Jammed(op) => {
+                report(&cpu.borrow(), &format!("jammed by opcode {op:#04x}"));
+                std::process::exit(1);
+            }
+        }
     }
 }
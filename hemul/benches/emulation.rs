@@ -0,0 +1,77 @@
+// Benchmarks of how fast the Cpu runs programs. The throughput is in emulated clock cycles, so
+// the elements per second that criterion reports are the emulated clock rate, Melem/s being MHz.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use hemul::{
+    Byte, Resettable, Tickable,
+    cpu::{Cpu, Mode, Variant},
+    memory::Memory,
+};
+
+/// Clock cycles emulated per iteration
+const CYCLES: u64 = 100_000;
+
+/// Programs that loop forever, starting at $0000 where the zeroed reset vector points
+const PROGRAMS: [(&str, &[Byte]); 3] = [
+    (
+        "arithmetic",
+        &[
+            0xA2, 0x00, // LDX #$00
+            0x8A, //       loop: TXA
+            0x69, 0x03, // ADC #$03
+            0x95, 0x10, // STA $10,X
+            0xE8, //       INX
+            0xD0, 0xF8, // BNE loop
+            0x4C, 0x00, 0x00, // JMP $0000
+        ],
+    ),
+    (
+        "copy",
+        &[
+            0xA0, 0x00, //       LDY #$00
+            0xB9, 0x00, 0x10, // loop: LDA $1000,Y
+            0x99, 0x00, 0x20, // STA $2000,Y
+            0xC8, //             INY
+            0xD0, 0xF7, //       BNE loop
+            0x4C, 0x00, 0x00, // JMP $0000
+        ],
+    ),
+    (
+        "subroutine",
+        &[
+            0x20, 0x06, 0x00, // JSR $0006
+            0x4C, 0x00, 0x00, // JMP $0000
+            0x48, //             PHA
+            0x68, //             PLA
+            0x60, //             RTS
+        ],
+    ),
+];
+
+fn bench_variant(c: &mut Criterion, variant: Variant) {
+    for (name, program) in PROGRAMS {
+        let mut group = c.benchmark_group(format!("{variant:?}/{name}"));
+        group.throughput(Throughput::Elements(CYCLES));
+        for mode in [Mode::Fast, Mode::Original, Mode::Stepped] {
+            let mut cpu = Cpu::with_variant_and_mode(Memory::from(program), variant, mode);
+            cpu.reset().expect("Resetting CPU failed");
+            group.bench_function(BenchmarkId::from_parameter(format!("{mode:?}")), |b| {
+                b.iter(|| {
+                    let end = cpu.cycles_get() + CYCLES;
+                    while cpu.cycles_get() < end {
+                        cpu.tick().expect("Tick failed");
+                    }
+                });
+            });
+        }
+        group.finish();
+    }
+}
+
+fn bench_emulation(c: &mut Criterion) {
+    bench_variant(c, Variant::Nmos6502);
+    bench_variant(c, Variant::Wdc65C02);
+}
+
+criterion_group!(benches, bench_emulation);
+criterion_main!(benches);
@@ -0,0 +1,139 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{Byte, Device, Snapshottable, Word};
+
+/// Handle to the bank that is mapped into a [`Banked`] window.
+///
+/// Cloning a handle gives a new handle to the same selection, so a latch register or any other
+/// device holding one can switch banks while the Cpu runs.
+#[derive(Debug, Default, Clone)]
+pub struct Select(Rc<Cell<usize>>);
+
+impl Select {
+    /// Bank that is mapped in
+    pub fn get(&self) -> usize {
+        self.0.get()
+    }
+
+    /// Map in a bank
+    pub fn set(&self, bank: usize) {
+        self.0.set(bank);
+    }
+}
+
+/// A window that one of several devices is mapped into at a time.
+///
+/// Banks are like the RAM banks of a memory card or the ROM and RAM that share addresses on the
+/// C64. Reads, writes and snapshots of the bus see the bank that is mapped in when they happen,
+/// and selecting a bank that does not exist leaves the window empty. A snapshot of the window
+/// itself has every bank.
+pub struct Banked {
+    banks: Vec<Box<dyn Device>>,
+    select: Select,
+}
+
+impl Banked {
+    /// Banked window with the first bank mapped in
+    pub fn new(banks: Vec<Box<dyn Device>>) -> Self {
+        Self {
+            banks,
+            select: Select::default(),
+        }
+    }
+
+    /// Handle to switch banks with
+    pub fn select(&self) -> Select {
+        self.select.clone()
+    }
+
+    fn bank(&self) -> Option<&dyn Device> {
+        self.banks.get(self.select.get()).map(AsRef::as_ref)
+    }
+
+    fn bank_mut(&mut self) -> Option<&mut Box<dyn Device>> {
+        self.banks.get_mut(self.select.get())
+    }
+}
+
+impl Device for Banked {
+    fn inside_bounds(&self, addr: Word) -> bool {
+        self.bank().is_some_and(|bank| bank.inside_bounds(addr))
+    }
+
+    fn writable(&self, addr: Word) -> bool {
+        self.bank().is_none_or(|bank| bank.writable(addr))
+    }
+
+    fn peek(&self, addr: Word) -> Byte {
+        self.bank().map(|bank| bank.peek(addr)).unwrap_or_default()
+    }
+
+    fn read(&mut self, addr: Word) -> Byte {
+        self.bank_mut()
+            .map(|bank| bank.read(addr))
+            .unwrap_or_default()
+    }
+
+    fn write(&mut self, addr: Word, data: Byte) {
+        if let Some(bank) = self.bank_mut() {
+            bank.write(addr, data);
+        }
+    }
+}
+
+/// Contents of every bank of a [`Banked`] window, and the bank that is mapped in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Bank that is mapped in
+    pub selected: usize,
+    /// Bytes of each bank, from its first address up to where it ends
+    pub banks: Vec<Vec<Byte>>,
+}
+
+impl Snapshottable for Banked {
+    type Snapshot = Snapshot;
+
+    fn snapshot(&self) -> Result<Self::Snapshot, crate::Error> {
+        let banks = self
+            .banks
+            .iter()
+            .map(|bank| {
+                (0..=Word::MAX)
+                    .take_while(|&addr| bank.inside_bounds(addr))
+                    .map(|addr| bank.peek(addr))
+                    .collect()
+            })
+            .collect();
+        Ok(Snapshot {
+            selected: self.select.get(),
+            banks,
+        })
+    }
+}
+
+/// Latch register that selects a bank with the byte written to it, masked to the number of banks
+/// the board decodes. Reading it returns the bank that is mapped in.
+pub struct Latch {
+    select: Select,
+    mask: Byte,
+}
+
+impl Latch {
+    pub fn new(select: Select, mask: Byte) -> Self {
+        Self { select, mask }
+    }
+}
+
+impl Device for Latch {
+    fn inside_bounds(&self, _addr: Word) -> bool {
+        true
+    }
+
+    fn peek(&self, _addr: Word) -> Byte {
+        Byte::try_from(self.select.get()).unwrap_or(Byte::MAX)
+    }
+
+    fn write(&mut self, _addr: Word, data: Byte) {
+        self.select.set(usize::from(data & self.mask));
+    }
+}
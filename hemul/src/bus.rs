@@ -1,76 +1,374 @@
-use std::{
-    error::Error,
-    ops::{Index, IndexMut},
-};
+use std::ops::{BitAnd, Sub};
 
-use crate::{Addressable, Byte, Snapshottable, Word};
+use thiserror::Error;
 
+use crate::{Addressable, Byte, Device, Long, Snapshottable, Word};
+
+/// Errors of the bus, either an access that no device answered or devices that do not fit
+#[allow(clippy::module_name_repetitions)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BusError {
+    #[error("memory location out of bounds `{0:#06x}`")]
+    OutOfBounds(Word),
+
+    #[error("memory location is read only `{0:#06x}`")]
+    ReadOnly(Word),
+
+    /// The range is where the windows of the two devices overlap
+    #[error("device '{name}' overlaps '{other}' at `{start:#06x}-{end:#06x}`")]
+    Overlap {
+        name: String,
+        other: String,
+        start: Long,
+        end: Long,
+    },
+}
+
+/// Addresses of a bus, a [`Word`] on the 6502 and a [`Long`] on the 65C816
+pub trait Width:
+    Copy + Ord + Into<Long> + std::fmt::LowerHex + Sub<Output = Self> + BitAnd<Output = Self>
+{
+    /// Highest address on the bus
+    const MAX: Self;
+
+    /// Position of the address in the address space
+    fn index(self) -> usize;
+
+    /// Address at a position in the address space
+    fn from_index(index: usize) -> Self;
+}
+
+impl Width for Word {
+    const MAX: Self = Self::MAX;
+
+    fn index(self) -> usize {
+        usize::from(self)
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self::try_from(index).unwrap_or(Self::MAX)
+    }
+}
+
+impl Width for Long {
+    /// The top byte is not on the bus
+    const MAX: Self = 0x00FF_FFFF;
+
+    fn index(self) -> usize {
+        usize::try_from(self).unwrap_or(usize::MAX)
+    }
+
+    fn from_index(index: usize) -> Self {
+        Self::try_from(index).unwrap_or(<Self as Width>::MAX)
+    }
+}
+
+/// What the bus does on an access to an address that no device is connected at
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Unmapped {
+    /// Reads return the last byte that was on the data bus, like on real hardware where nothing
+    /// drives it and the lines hold their charge
+    OpenBus,
+    /// Reads return the value
+    Value(Byte),
+    /// The access is a bus fault that the Cpu fails with
+    #[default]
+    Fault,
+}
+
+/// A device connected to a window of the address space
+struct Window<A> {
+    /// Name the device was connected with
+    name: String,
+    start: A,
+    end: A,
+    /// Mask applied to the offset in the window, so a small device repeats across a larger one
+    mask: A,
+    device: Box<dyn Device<A>>,
+}
+
+impl<A: Width> Window<A> {
+    /// Address the device sees for an address in the window
+    fn offset(&self, addr: A) -> A {
+        (addr - self.start) & self.mask
+    }
+}
+
+/// Which device answers in a page of 256 bytes
 #[derive(Default)]
-pub struct Bus {
-    devices: Vec<(String, Word, Word, Box<dyn Addressable>)>,
+enum Page {
+    #[default]
+    Unmapped,
+    /// One device covers the whole page
+    Device(usize),
+    /// Devices cover parts of the page, each address decoded on its own
+    Split(Box<[Option<usize>; 256]>),
 }
 
-impl Bus {
+/// Devices connected to windows of the address space. Each device is addressed relative to the
+/// start of its window, so a device at `$6000` sees an access to `$6001` as one to `$0001`.
+///
+/// Addresses are decoded through a table of pages, so finding the device takes the same time
+/// however many are connected. The bus of the 6502 has [`Word`] addresses, and that of the
+/// 65C816 has [`Long`] ones.
+///
+/// The bus is a [`Device`] rather than [`Addressable`], since reading a device can change it and
+/// so can not hand out references to its bytes. Read it with [`Device::peek`] and
+/// [`Device::read`] instead of indexing, and connect boxed [`Addressable`] devices with
+/// [`Bus::connect_addressable`].
+pub struct Bus<A = Word> {
+    devices: Vec<Window<A>>,
+    pages: Box<[Page]>,
+
+    /// What accesses to unmapped addresses do
+    unmapped: Unmapped,
+
+    /// Last byte read or written, which is what an open bus reads as
+    data: Byte,
+
+    /// Whether writes to unmapped addresses are logged
+    #[cfg(feature = "tracing")]
+    log_unmapped: bool,
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Bus<Long> {
+    /// Bus with the 24 bit addresses of the 65C816
+    pub fn long() -> Self {
+        Self::empty()
+    }
+}
+
+impl<A: Width> Bus<A> {
+    /// Bus without any devices
+    fn empty() -> Self {
+        Self {
+            devices: Vec::new(),
+            pages: std::iter::repeat_with(Page::default)
+                .take((A::MAX.index() >> 8) + 1)
+                .collect(),
+            unmapped: Unmapped::default(),
+            data: 0,
+            #[cfg(feature = "tracing")]
+            log_unmapped: false,
+        }
+    }
+
+    /// Set what accesses to unmapped addresses do, which is a bus fault by default
+    pub fn unmapped_set(&mut self, unmapped: Unmapped) {
+        self.unmapped = unmapped;
+    }
+
+    /// Log writes to unmapped addresses, which are otherwise dropped silently when they are not
+    /// a fault
+    #[cfg(feature = "tracing")]
+    pub fn log_unmapped_set(&mut self, log: bool) {
+        self.log_unmapped = log;
+    }
+
+    /// Connect a device to a window, failing if it overlaps the window of another device
     pub fn connect(
+        &mut self,
+        name: impl Into<String>,
+        start: A,
+        end: A,
+        device: Box<dyn Device<A>>,
+    ) -> Result<(), BusError> {
+        self.connect_mirrored(name, start, end, A::MAX, device)
+    }
+
+    /// Connect a device that only decodes the address bits in the mask, so it repeats across the
+    /// window like on boards with incomplete address decoding. A 256 byte RAM at `$6000-$6FFF`
+    /// with the mask `$00FF` answers at `$6000`, `$6100` and so on.
+    pub fn connect_mirrored(
+        &mut self,
+        name: impl Into<String>,
+        start: A,
+        end: A,
+        mask: A,
+        device: Box<dyn Device<A>>,
+    ) -> Result<(), BusError> {
+        let name = name.into();
+        if let Some(other) = self
+            .devices
+            .iter()
+            .find(|window| window.start <= end && start <= window.end)
+        {
+            return Err(BusError::Overlap {
+                name,
+                other: other.name.clone(),
+                start: start.max(other.start).into(),
+                end: end.min(other.end).into(),
+            });
+        }
+
+        let index = self.devices.len();
+        self.devices.push(Window {
+            name,
+            start,
+            end,
+            mask,
+            device,
+        });
+        // Nothing answers above the highest address
+        self.map(start.index(), end.min(A::MAX).index(), index);
+        Ok(())
+    }
+
+    /// Point the pages of a window to the device
+    fn map(&mut self, start: usize, end: usize, index: usize) {
+        for page in start >> 8..=end >> 8 {
+            let first = page << 8;
+            let last = first | 0xFF;
+            let entry = &mut self.pages[page];
+            if start <= first && last <= end {
+                *entry = Page::Device(index);
+                continue;
+            }
+
+            if !matches!(entry, Page::Split(_)) {
+                *entry = Page::Split(Box::new([None; 256]));
+            }
+            if let Page::Split(addrs) = entry {
+                for addr in start.max(first)..=end.min(last) {
+                    addrs[addr & 0xFF] = Some(index);
+                }
+            }
+        }
+    }
+
+    /// Index of the device connected at the address
+    fn decode(&self, addr: A) -> Option<usize> {
+        let addr = addr.index();
+        match self.pages.get(addr >> 8)? {
+            Page::Unmapped => None,
+            Page::Device(index) => Some(*index),
+            Page::Split(addrs) => addrs[addr & 0xFF],
+        }
+    }
+}
+
+impl Bus {
+    /// Connect a boxed [`Addressable`] device to a window, like [`Bus::connect`]
+    pub fn connect_addressable(
         &mut self,
         name: impl Into<String>,
         start: Word,
         end: Word,
         device: Box<dyn Addressable>,
-    ) {
-        self.devices.push((name.into(), start, end, device));
+    ) -> Result<(), BusError> {
+        self.connect(name, start, end, Box::new(device))
     }
 }
 
-impl Addressable for Bus {
-    fn inside_bounds(&self, addr: Word) -> bool {
-        for (_, _, _, device) in &self.devices {
-            if device.inside_bounds(addr) {
-                return true;
-            }
-        }
-        false
+impl<A: Width> std::fmt::Debug for Bus<A> {
+    /// Devices by name, with their windows
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.devices.iter().map(|window| {
+                (
+                    &window.name,
+                    format!("{:#06x}-{:#06x}", window.start, window.end),
+                )
+            }))
+            .finish()
     }
 }
 
-impl Index<Word> for Bus {
-    type Output = Byte;
+impl<A: Width> Bus<A> {
+    /// Device connected at the address, with the address it sees
+    fn device(&self, addr: A) -> Option<(&dyn Device<A>, A)> {
+        self.decode(addr)
+            .map(|index| &self.devices[index])
+            .map(|window| (window.device.as_ref(), window.offset(addr)))
+    }
 
-    fn index(&self, index: Word) -> &Self::Output {
-        for (_, start, end, device) in &self.devices {
-            if *start <= index && index <= *end {
-                return device.index(index);
-            }
-        }
-        panic!("Indexed to unknown device")
+    /// Device connected at the address, to access it
+    fn device_mut(&mut self, addr: A) -> Option<(&mut Box<dyn Device<A>>, A)> {
+        self.decode(addr)
+            .map(|index| &mut self.devices[index])
+            .map(|window| {
+                let offset = window.offset(addr);
+                (&mut window.device, offset)
+            })
     }
 }
 
-impl IndexMut<Word> for Bus {
-    fn index_mut(&mut self, index: Word) -> &mut Self::Output {
-        for (_, start, end, device) in &mut self.devices {
-            if *start <= index && index <= *end {
-                return device.index_mut(index);
-            }
+impl<A: Width> Device<A> for Bus<A> {
+    /// Whether a device answers at the address, or the bus does for unmapped addresses unless
+    /// they are a fault
+    fn inside_bounds(&self, addr: A) -> bool {
+        match self.device(addr) {
+            Some((device, offset)) => device.inside_bounds(offset),
+            None => self.unmapped != Unmapped::Fault,
+        }
+    }
+
+    /// Whether the device at the address takes writes, writes to unmapped addresses are never
+    /// refused here
+    fn writable(&self, addr: A) -> bool {
+        self.device(addr)
+            .is_none_or(|(device, offset)| device.writable(offset))
+    }
+
+    fn peek(&self, addr: A) -> Byte {
+        match self.device(addr) {
+            Some((device, offset)) => device.peek(offset),
+            None => match self.unmapped {
+                Unmapped::OpenBus => self.data,
+                Unmapped::Value(value) => value,
+                Unmapped::Fault => 0,
+            },
+        }
+    }
+
+    fn read(&mut self, addr: A) -> Byte {
+        self.data = match self.device_mut(addr) {
+            Some((device, offset)) => device.read(offset),
+            None => self.peek(addr),
+        };
+        self.data
+    }
+
+    fn write(&mut self, addr: A, data: Byte) {
+        self.data = data;
+        if let Some((device, offset)) = self.device_mut(addr) {
+            device.write(offset, data);
+            return;
+        }
+
+        #[cfg(feature = "tracing")]
+        if self.log_unmapped {
+            let addr: Long = addr.into();
+            tracing::warn!(target: "hemul::bus", addr, data, "write to unmapped address");
         }
-        panic!("Indexed to unknown device")
     }
 }
 
-impl Snapshottable for Bus {
+impl<A: Width> Snapshottable for Bus<A> {
     type Snapshot = Vec<Byte>;
 
-    fn snapshot(&self) -> Result<Self::Snapshot, Box<dyn Error>> {
-        let mut end = Word::MIN;
-        for (_, _, e, _) in &self.devices {
-            if &end < e {
-                end = *e;
-            }
-        }
-        let mut dump = vec![0; end as usize];
-        for (_, start, end, device) in &self.devices {
-            for i in *start..=*end {
-                dump[i as usize] = device[i];
+    fn snapshot(&self) -> Result<Self::Snapshot, crate::Error> {
+        let end = self
+            .devices
+            .iter()
+            .map(|window| window.end.min(A::MAX).index())
+            .max()
+            .unwrap_or_default();
+        let mut dump = vec![0; end + 1];
+        for window in &self.devices {
+            let start = window.start.index();
+            let end = window.end.min(A::MAX).index();
+            for (byte, i) in dump[start..=end].iter_mut().zip(start..) {
+                let offset = window.offset(A::from_index(i));
+                if window.device.inside_bounds(offset) {
+                    *byte = window.device.peek(offset);
+                }
             }
         }
         Ok(dump)
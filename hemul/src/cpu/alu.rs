@@ -0,0 +1,502 @@
+use super::PFlag;
+use crate::Byte;
+
+/// Result of an ALU operation together with the status flags it produced. Flags the operation
+/// does not affect are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Output {
+    /// The computed value
+    pub result: Byte,
+    /// Carry Flag
+    pub c: Option<PFlag>,
+    /// Zero Flag
+    pub z: Option<PFlag>,
+    /// Overflow Flag
+    pub v: Option<PFlag>,
+    /// Negative Flag
+    pub n: Option<PFlag>,
+}
+
+impl From<Byte> for Output {
+    /// Output that leaves all flags alone
+    fn from(result: Byte) -> Self {
+        Self {
+            result,
+            c: None,
+            z: None,
+            v: None,
+            n: None,
+        }
+    }
+}
+
+impl Output {
+    /// Output that sets Z and N from the result
+    fn zn(result: Byte) -> Self {
+        Self {
+            result,
+            c: None,
+            z: Some(result == 0),
+            v: None,
+            n: Some(result & 0b1000_0000 > 0),
+        }
+    }
+
+    fn with_c(mut self, c: PFlag) -> Self {
+        self.c = Some(c);
+        self
+    }
+
+    fn with_v(mut self, v: PFlag) -> Self {
+        self.v = Some(v);
+        self
+    }
+}
+
+/// ADC - Add with Carry
+/// ```text
+/// A,Z,C,N,V = A+M+C
+/// ```
+/// In decimal mode only the carry is meaningful, but N, V and Z are left exactly as the NMOS
+/// 6502 leaves them, see <http://www.6502.org/tutorials/decimal_mode.html#A>
+pub fn adc(a: Byte, m: Byte, carry: PFlag, decimal: bool) -> Output {
+    if decimal {
+        return adc_decimal(a, m, carry);
+    }
+
+    let sum = u16::from(a) + u16::from(m) + u16::from(carry);
+    let result = sum.to_le_bytes()[0];
+    Output::zn(result)
+        .with_c(sum > 0xFF)
+        .with_v((a ^ result) & (m ^ result) & 0b1000_0000 > 0)
+}
+
+fn adc_decimal(a: Byte, m: Byte, carry: PFlag) -> Output {
+    let (wa, wm, wc) = (i16::from(a), i16::from(m), i16::from(carry));
+
+    let mut lo = (wa & 0x0F) + (wm & 0x0F) + wc;
+    if lo >= 0x0A {
+        lo = ((lo + 0x06) & 0x0F) + 0x10;
+    }
+    let mut res = (wa & 0xF0) + (wm & 0xF0) + lo;
+
+    // N and V are taken before the high nibble is adjusted, V using signed arithmetic
+    let signed = i16::from((a & 0xF0).cast_signed()) + i16::from((m & 0xF0).cast_signed()) + lo;
+    let n = res & 0x80 > 0;
+    let v = !(-128..=127).contains(&signed);
+
+    if res >= 0xA0 {
+        res += 0x60;
+    }
+
+    // Z is set from the binary sum
+    Output {
+        result: res.to_le_bytes()[0],
+        c: Some(res > 0xFF),
+        z: Some((wa + wm + wc).to_le_bytes()[0] == 0),
+        v: Some(v),
+        n: Some(n),
+    }
+}
+
+/// SBC - Subtract with Carry
+/// ```text
+/// A,Z,C,N,V = A-M-(1-C)
+/// ```
+/// In decimal mode all flags are set as if the subtraction was done in binary, like the NMOS
+/// 6502 does, see <http://www.6502.org/tutorials/decimal_mode.html#A>
+pub fn sbc(a: Byte, m: Byte, carry: PFlag, decimal: bool) -> Output {
+    // Subtraction is addition of the ones' complement
+    let binary = adc(a, !m, carry, false);
+    if !decimal {
+        return binary;
+    }
+
+    let (a, m, c) = (i16::from(a), i16::from(m), i16::from(carry));
+    let mut lo = (a & 0x0F) - (m & 0x0F) + c - 1;
+    if lo < 0 {
+        lo = ((lo - 0x06) & 0x0F) - 0x10;
+    }
+    let mut res = (a & 0xF0) - (m & 0xF0) + lo;
+    if res < 0 {
+        res -= 0x60;
+    }
+
+    Output {
+        result: res.to_le_bytes()[0],
+        ..binary
+    }
+}
+
+/// ADC - Add with Carry, as done by the 65C02
+/// ```text
+/// A,Z,C,N,V = A+M+C
+/// ```
+/// The accumulator, C and V are the same as on the NMOS 6502, but in decimal mode N and Z are
+/// valid for the decimal result.
+pub fn adc_cmos(a: Byte, m: Byte, carry: PFlag, decimal: bool) -> Output {
+    let out = adc(a, m, carry, decimal);
+    Output {
+        v: out.v,
+        ..Output::zn(out.result).with_c(out.c.unwrap_or_default())
+    }
+}
+
+/// SBC - Subtract with Carry, as done by the 65C02
+/// ```text
+/// A,Z,C,N,V = A-M-(1-C)
+/// ```
+/// In decimal mode C and V are set as if the subtraction was done in binary, while N and Z are
+/// valid for the decimal result, see <http://www.6502.org/tutorials/decimal_mode.html#A>
+pub fn sbc_cmos(a: Byte, m: Byte, carry: PFlag, decimal: bool) -> Output {
+    let binary = adc(a, !m, carry, false);
+    if !decimal {
+        return binary;
+    }
+
+    // Sequence 4 from the tutorial
+    let (a, m, c) = (i16::from(a), i16::from(m), i16::from(carry));
+    let lo = (a & 0x0F) - (m & 0x0F) + c - 1;
+    let mut res = a - m + c - 1;
+    if res < 0 {
+        res -= 0x60;
+    }
+    if lo < 0 {
+        res -= 0x06;
+    }
+
+    Output {
+        c: binary.c,
+        v: binary.v,
+        ..Output::zn(res.to_le_bytes()[0])
+    }
+}
+
+/// CMP, CPX, CPY - Compare a register with memory
+/// ```text
+/// Z,C,N = R-M
+/// ```
+/// The result is the register value itself, as comparing does not change it.
+pub fn compare(r: Byte, m: Byte) -> Output {
+    Output {
+        result: r,
+        ..Output::zn(r.wrapping_sub(m)).with_c(r >= m)
+    }
+}
+
+/// BIT - Bit Test
+/// ```text
+/// A & M, N = M7, V = M6
+/// ```
+/// The result is the accumulator itself, as bit tests do not change it.
+pub fn bit(a: Byte, m: Byte) -> Output {
+    Output {
+        result: a,
+        c: None,
+        z: Some(a & m == 0),
+        v: Some(m & 0b0100_0000 > 0),
+        n: Some(m & 0b1000_0000 > 0),
+    }
+}
+
+/// ASL - Arithmetic Shift Left
+/// ```text
+/// A,Z,C,N = M*2 or M,Z,C,N = M*2
+/// ```
+pub fn asl(m: Byte) -> Output {
+    Output::zn(m << 1).with_c(m & 0b1000_0000 > 0)
+}
+
+/// LSR - Logical Shift Right
+/// ```text
+/// A,C,Z,N = A/2 or M,C,Z,N = M/2
+/// ```
+pub fn lsr(m: Byte) -> Output {
+    Output::zn(m >> 1).with_c(m & 0b0000_0001 > 0)
+}
+
+/// ROL - Rotate Left
+pub fn rol(m: Byte, carry: PFlag) -> Output {
+    Output::zn((m << 1) | Byte::from(carry)).with_c(m & 0b1000_0000 > 0)
+}
+
+/// ROR - Rotate Right
+pub fn ror(m: Byte, carry: PFlag) -> Output {
+    Output::zn((m >> 1) | (Byte::from(carry) << 7)).with_c(m & 0b0000_0001 > 0)
+}
+
+/// INC - Increment Memory
+/// ```text
+/// M,Z,N = M+1
+/// ```
+pub fn inc(m: Byte) -> Output {
+    Output::zn(m.wrapping_add(1))
+}
+
+/// DEC - Decrement Memory
+/// ```text
+/// M,Z,N = M-1
+/// ```
+pub fn dec(m: Byte) -> Output {
+    Output::zn(m.wrapping_sub(1))
+}
+
+/// TRB - Test and Reset Bits
+/// ```text
+/// Z = A&M, M = M&~A
+/// ```
+pub fn trb(a: Byte, m: Byte) -> Output {
+    Output {
+        z: Some(a & m == 0),
+        ..Output::from(m & !a)
+    }
+}
+
+/// TSB - Test and Set Bits
+/// ```text
+/// Z = A&M, M = M|A
+/// ```
+pub fn tsb(a: Byte, m: Byte) -> Output {
+    Output {
+        z: Some(a & m == 0),
+        ..Output::from(m | a)
+    }
+}
+
+/// RMB - Reset Memory Bit
+/// ```text
+/// M = M&~(1<<bit)
+/// ```
+pub fn rmb(m: Byte, bit: u8) -> Output {
+    Output::from(m & !(1 << bit))
+}
+
+/// SMB - Set Memory Bit
+/// ```text
+/// M = M|(1<<bit)
+/// ```
+pub fn smb(m: Byte, bit: u8) -> Output {
+    Output::from(m | (1 << bit))
+}
+
+/// ARR - Rotate right after the AND of the undocumented ARR opcode
+/// ```text
+/// A,Z,N = M/2+C*128, C = A6, V = A6^A5
+/// ```
+/// In decimal mode N, Z and V follow the binary rotation while the result gets a BCD style fix
+/// up and the carry tells if the high nibble was adjusted, see
+/// <https://www.zimmers.net/anonftp/pub/cbm/documents/chipdata/64doc>
+pub fn arr(m: Byte, carry: PFlag, decimal: bool) -> Output {
+    let rotated = (m >> 1) | (Byte::from(carry) << 7);
+    if !decimal {
+        return Output::zn(rotated)
+            .with_c(rotated & 0b0100_0000 > 0)
+            .with_v((rotated ^ (rotated << 1)) & 0b0100_0000 > 0);
+    }
+
+    let mut result = rotated;
+    if (m & 0x0F) + (m & 0x01) > 0x05 {
+        result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+    }
+    let c = u16::from(m & 0xF0) + u16::from(m & 0x10) > 0x50;
+    if c {
+        result = result.wrapping_add(0x60);
+    }
+
+    Output {
+        result,
+        c: Some(c),
+        z: Some(rotated == 0),
+        v: Some((m ^ rotated) & 0b0100_0000 > 0),
+        n: Some(carry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all() -> impl Iterator<Item = (Byte, Byte, PFlag)> {
+        (0..=255u8)
+            .flat_map(|a| (0..=255u8).flat_map(move |m| [false, true].map(move |c| (a, m, c))))
+    }
+
+    fn signed(v: Byte) -> i16 {
+        i16::from(v.cast_signed())
+    }
+
+    fn bcd(n: u8) -> Byte {
+        ((n / 10) << 4) | (n % 10)
+    }
+
+    #[test]
+    fn test_adc_binary() {
+        for (a, m, c) in all() {
+            let sum = u16::from(a) + u16::from(m) + u16::from(c);
+            let signed_sum = signed(a) + signed(m) + i16::from(c);
+            let out = adc(a, m, c, false);
+            assert_eq!(
+                (out.result, out.c, out.z, out.n, out.v),
+                (
+                    sum.to_le_bytes()[0],
+                    Some(sum > 0xFF),
+                    Some(sum & 0xFF == 0),
+                    Some(sum & 0x80 > 0),
+                    Some(!(-128..=127).contains(&signed_sum)),
+                ),
+                "{a:#04x} + {m:#04x} + {c}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sbc_binary() {
+        for (a, m, c) in all() {
+            let diff = i16::from(a) - i16::from(m) - i16::from(!c);
+            let signed_diff = signed(a) - signed(m) - i16::from(!c);
+            let out = sbc(a, m, c, false);
+            assert_eq!(
+                (out.result, out.c, out.z, out.n, out.v),
+                (
+                    diff.to_le_bytes()[0],
+                    Some(diff >= 0),
+                    Some(diff & 0xFF == 0),
+                    Some(diff & 0x80 > 0),
+                    Some(!(-128..=127).contains(&signed_diff)),
+                ),
+                "{a:#04x} - {m:#04x} - {}",
+                !c
+            );
+        }
+    }
+
+    #[test]
+    fn test_sbc_decimal_flags_are_binary() {
+        for (a, m, c) in all() {
+            let binary = sbc(a, m, c, false);
+            let decimal = sbc(a, m, c, true);
+            assert_eq!(
+                (decimal.c, decimal.z, decimal.v, decimal.n),
+                (binary.c, binary.z, binary.v, binary.n)
+            );
+        }
+    }
+
+    #[test]
+    fn test_decimal_cmos() {
+        for (a, m, c) in all() {
+            for (nmos, cmos) in [
+                (adc(a, m, c, true), adc_cmos(a, m, c, true)),
+                (sbc(a, m, c, true), sbc_cmos(a, m, c, true)),
+            ] {
+                assert_eq!(cmos.z, Some(cmos.result == 0));
+                assert_eq!(cmos.n, Some(cmos.result >> 7 == 1));
+                assert_eq!((cmos.c, cmos.v), (nmos.c, nmos.v));
+            }
+        }
+
+        for a in 0..100u8 {
+            for m in 0..100u8 {
+                for c in [false, true] {
+                    assert_eq!(
+                        adc_cmos(bcd(a), bcd(m), c, true).result,
+                        adc(bcd(a), bcd(m), c, true).result
+                    );
+                    assert_eq!(
+                        sbc_cmos(bcd(a), bcd(m), c, true).result,
+                        sbc(bcd(a), bcd(m), c, true).result
+                    );
+                }
+            }
+        }
+
+        for (a, m, c) in all() {
+            assert_eq!(adc_cmos(a, m, c, false), adc(a, m, c, false));
+            assert_eq!(sbc_cmos(a, m, c, false), sbc(a, m, c, false));
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        for (r, m, _) in all().filter(|(_, _, c)| !c) {
+            let out = compare(r, m);
+            assert_eq!(out.result, r);
+            assert_eq!(out.c, Some(r >= m));
+            assert_eq!(out.z, Some(r == m));
+            assert_eq!(out.n, Some(r.wrapping_sub(m) & 0x80 > 0));
+            assert_eq!(out.v, None);
+        }
+    }
+
+    #[test]
+    fn test_bit() {
+        for (a, m, _) in all().filter(|(_, _, c)| !c) {
+            let out = bit(a, m);
+            assert_eq!(out.result, a);
+            assert_eq!(out.c, None);
+            assert_eq!(out.z, Some(a & m == 0));
+            assert_eq!(out.n, Some(m >> 7 == 1));
+            assert_eq!(out.v, Some((m >> 6) & 1 == 1));
+        }
+    }
+
+    #[test]
+    fn test_shifts() {
+        for m in 0..=255u8 {
+            for c in [false, true] {
+                let wide = u16::from(m);
+                for (out, expected, carry) in [
+                    (asl(m), wide << 1, m >> 7 == 1),
+                    (lsr(m), wide >> 1, m & 1 == 1),
+                    (rol(m, c), (wide << 1) | u16::from(c), m >> 7 == 1),
+                    (ror(m, c), (wide >> 1) | (u16::from(c) << 7), m & 1 == 1),
+                ] {
+                    let expected = expected.to_le_bytes()[0];
+                    assert_eq!(out.result, expected);
+                    assert_eq!(out.c, Some(carry));
+                    assert_eq!(out.z, Some(expected == 0));
+                    assert_eq!(out.n, Some(expected >> 7 == 1));
+                    assert_eq!(out.v, None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_memory() {
+        for (a, m, _) in all().filter(|(_, _, c)| !c) {
+            assert_eq!(inc(m), Output::zn(m.wrapping_add(1)));
+            assert_eq!(dec(m), Output::zn(m.wrapping_sub(1)));
+            assert_eq!(trb(a, m).result, m & !a);
+            assert_eq!(tsb(a, m).result, m | a);
+            assert_eq!(trb(a, m).z, Some(a & m == 0));
+            assert_eq!(tsb(a, m).z, Some(a & m == 0));
+            assert_eq!(tsb(a, m).n, None);
+        }
+        assert_eq!(rmb(0xFF, 3), Output::from(0xF7));
+        assert_eq!(smb(0x00, 7), Output::from(0x80));
+    }
+
+    #[test]
+    fn test_arr() {
+        for m in 0..=255u8 {
+            for c in [false, true] {
+                let rotated = ror(m, c).result;
+                let out = arr(m, c, false);
+                assert_eq!(out.result, rotated);
+                assert_eq!(out.c, Some(rotated >> 6 & 1 == 1));
+                assert_eq!(out.v, Some((rotated >> 6 & 1) != (rotated >> 5 & 1)));
+
+                let out = arr(m, c, true);
+                assert_eq!(out.z, Some(rotated == 0));
+                assert_eq!(out.n, Some(c));
+            }
+        }
+
+        // The high nibble is adjusted and carries, the low nibble is adjusted
+        assert_eq!(arr(0xFF, false, true).result, 0xD5);
+        assert_eq!(arr(0xFF, false, true).c, Some(true));
+        assert_eq!(arr(0x22, true, true).result, 0x91);
+        assert_eq!(arr(0x22, true, true).c, Some(false));
+    }
+}
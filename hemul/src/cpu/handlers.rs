@@ -0,0 +1,753 @@
+//! Handlers of the instructions, one for each op code. The decoder looks them up once for each
+//! op code, so running an instruction calls its handler without matching on it.
+
+use super::{
+    ANE_MAGIC, Cpu, Fault, IRQB, Variant,
+    address::Address,
+    alu,
+    instructions::{AddressMode, Cycles, Op, OpCode},
+    status::Status,
+};
+use crate::{Device, RunState};
+
+/// Runs an instruction after its op code is fetched, returning the cycles it used on top of
+/// those of the op
+pub(super) type Handler<T> = fn(&mut Cpu<T>, Op) -> Result<u8, Fault>;
+
+macro_rules! flags_zn {
+    ($self:ident, $r:expr) => {
+        $self.Z = $r == 0;
+        $self.N = ($r & 0b1000_0000) > 0;
+    };
+}
+
+macro_rules! compare {
+    ($self:ident, $r:ident, $mode:ident) => {
+        let addr = $self.fetch_addr($mode)?;
+        let data = $self.read(addr)?;
+        $self.alu(alu::compare($self.$r, data));
+    };
+}
+
+/// Branch on a condition, returning the extra cycles used: +1 if taken, +2 if to a new page
+macro_rules! branch {
+    ($self:ident, $cond:expr) => {{
+        let offset = $self.fetch()?;
+        let pc = $self.PC;
+        let target = pc.wrapping_add_signed(i16::from(offset.cast_signed()));
+        $self.target(target);
+        if $cond {
+            // The next opcode is read while the offset is added to the low byte of the PC
+            $self.read(pc)?;
+            $self.PC = target;
+            if pc.to_be_bytes()[0] == $self.PC.to_be_bytes()[0] {
+                1
+            } else {
+                $self.read(Address::Full(
+                    $self.PC.to_le_bytes()[0],
+                    pc.to_le_bytes()[1],
+                ))?;
+                2
+            }
+        } else {
+            0
+        }
+    }};
+}
+
+impl<T> Cpu<T>
+where
+    T: Device,
+{
+    /// Handler of an instruction
+    pub(super) fn handler(op: OpCode) -> Handler<T> {
+        match op {
+            OpCode::Lda => Self::lda,
+            OpCode::Ldx => Self::ldx,
+            OpCode::Ldy => Self::ldy,
+            OpCode::Sta => Self::sta,
+            OpCode::Stx => Self::stx,
+            OpCode::Sty => Self::sty,
+            OpCode::Tax => Self::tax,
+            OpCode::Tay => Self::tay,
+            OpCode::Txa => Self::txa,
+            OpCode::Tya => Self::tya,
+            OpCode::Tsx => Self::tsx,
+            OpCode::Txs => Self::txs,
+            OpCode::Pha => Self::pha,
+            OpCode::Php => Self::php,
+            OpCode::Pla => Self::pla,
+            OpCode::Plp => Self::plp,
+            OpCode::And => Self::and,
+            OpCode::Eor => Self::eor,
+            OpCode::Ora => Self::ora,
+            OpCode::Bit => Self::bit,
+            OpCode::Adc => Self::adc,
+            OpCode::Sbc => Self::sbc,
+            OpCode::Cmp => Self::cmp,
+            OpCode::Cpx => Self::cpx,
+            OpCode::Cpy => Self::cpy,
+            OpCode::Inc => Self::inc,
+            OpCode::Inx => Self::inx,
+            OpCode::Iny => Self::iny,
+            OpCode::Dec => Self::dec,
+            OpCode::Dex => Self::dex,
+            OpCode::Dey => Self::dey,
+            OpCode::Asl => Self::asl,
+            OpCode::Lsr => Self::lsr,
+            OpCode::Rol => Self::rol,
+            OpCode::Ror => Self::ror,
+            OpCode::Jmp => Self::jmp,
+            OpCode::Jsr => Self::jsr,
+            OpCode::Rts => Self::rts,
+            OpCode::Bcc => Self::bcc,
+            OpCode::Bcs => Self::bcs,
+            OpCode::Beq => Self::beq,
+            OpCode::Bmi => Self::bmi,
+            OpCode::Bne => Self::bne,
+            OpCode::Bpl => Self::bpl,
+            OpCode::Bvc => Self::bvc,
+            OpCode::Bvs => Self::bvs,
+            OpCode::Clc => Self::clc,
+            OpCode::Cld => Self::cld,
+            OpCode::Cli => Self::cli,
+            OpCode::Clv => Self::clv,
+            OpCode::Sec => Self::sec,
+            OpCode::Sed => Self::sed,
+            OpCode::Sei => Self::sei,
+            OpCode::Brk => Self::brk,
+            OpCode::Rti => Self::rti,
+            OpCode::Nop => Self::nop,
+            OpCode::Slo => Self::slo,
+            OpCode::Rla => Self::rla,
+            OpCode::Sre => Self::sre,
+            OpCode::Rra => Self::rra,
+            OpCode::Sax => Self::sax,
+            OpCode::Lax => Self::lax,
+            OpCode::Dcp => Self::dcp,
+            OpCode::Isc => Self::isc,
+            OpCode::Anc => Self::anc,
+            OpCode::Alr => Self::alr,
+            OpCode::Arr => Self::arr,
+            OpCode::Sbx => Self::sbx,
+            OpCode::Ane => Self::ane,
+            OpCode::Lxa => Self::lxa,
+            OpCode::Las => Self::las,
+            OpCode::Sha => Self::sha,
+            OpCode::Shx => Self::shx,
+            OpCode::Shy => Self::shy,
+            OpCode::Tas => Self::tas,
+            OpCode::Jam => Self::jam,
+            OpCode::Bra => Self::bra,
+            OpCode::Phx => Self::phx,
+            OpCode::Phy => Self::phy,
+            OpCode::Plx => Self::plx,
+            OpCode::Ply => Self::ply,
+            OpCode::Stz => Self::stz,
+            OpCode::Trb => Self::trb,
+            OpCode::Tsb => Self::tsb,
+            OpCode::Wai => Self::wai,
+            OpCode::Stp => Self::stp,
+            OpCode::Bbr(_) => Self::bbr,
+            OpCode::Bbs(_) => Self::bbs,
+            OpCode::Rmb(_) => Self::rmb,
+            OpCode::Smb(_) => Self::smb,
+        }
+    }
+}
+
+// Every handler has the signature of a `Handler`, even those that can not fail
+#[allow(clippy::unnecessary_wraps)]
+impl<T> Cpu<T>
+where
+    T: Device,
+{
+    fn lda(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A = self.read(addr)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn ldx(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.X = self.read(addr)?;
+        flags_zn!(self, self.X);
+        Ok(0)
+    }
+
+    fn ldy(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.Y = self.read(addr)?;
+        flags_zn!(self, self.Y);
+        Ok(0)
+    }
+
+    fn sta(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.write(addr, self.A)?;
+        Ok(0)
+    }
+
+    fn stx(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.write(addr, self.X)?;
+        Ok(0)
+    }
+
+    fn sty(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.write(addr, self.Y)?;
+        Ok(0)
+    }
+
+    fn tax(&mut self, _: Op) -> Result<u8, Fault> {
+        self.X = self.A;
+        flags_zn!(self, self.X);
+        Ok(0)
+    }
+
+    fn tay(&mut self, _: Op) -> Result<u8, Fault> {
+        self.Y = self.A;
+        flags_zn!(self, self.Y);
+        Ok(0)
+    }
+
+    fn txa(&mut self, _: Op) -> Result<u8, Fault> {
+        self.A = self.X;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn tya(&mut self, _: Op) -> Result<u8, Fault> {
+        self.A = self.Y;
+        flags_zn!(self, self.Y);
+        Ok(0)
+    }
+
+    fn tsx(&mut self, _: Op) -> Result<u8, Fault> {
+        self.X = self.SP;
+        flags_zn!(self, self.X);
+        Ok(0)
+    }
+
+    fn txs(&mut self, _: Op) -> Result<u8, Fault> {
+        self.SP = self.X;
+        Ok(0)
+    }
+
+    fn pha(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_push(self.A)?;
+        Ok(0)
+    }
+
+    fn php(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_push(self.status_get().pushed(true))?;
+        Ok(0)
+    }
+
+    fn pla(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_read()?;
+        self.A = self.stack_pop()?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn plp(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_read()?;
+        let status = self.stack_pop()?;
+        self.status_set(Status::from(status));
+        Ok(0)
+    }
+
+    fn and(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A &= self.read(addr)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn eor(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A ^= self.read(addr)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn ora(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A |= self.read(addr)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn bit(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        let data = self.read(addr)?;
+        if mode == AddressMode::Immediate {
+            // There are no memory bits to copy into N and V
+            self.Z = self.A & data == 0;
+        } else {
+            self.alu(alu::bit(self.A, data));
+        }
+        Ok(0)
+    }
+
+    fn adc(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        let data = self.read(addr)?;
+        self.add(data);
+        if self.D && self.variant == Variant::Wdc65C02 {
+            self.read(addr)?;
+            return Ok(1);
+        }
+        Ok(0)
+    }
+
+    fn sbc(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        let data = self.read(addr)?;
+        self.sub(data);
+        if self.D && self.variant == Variant::Wdc65C02 {
+            self.read(addr)?;
+            return Ok(1);
+        }
+        Ok(0)
+    }
+
+    fn cmp(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        compare!(self, A, mode);
+        Ok(0)
+    }
+
+    fn cpx(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        compare!(self, X, mode);
+        Ok(0)
+    }
+
+    fn cpy(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        compare!(self, Y, mode);
+        Ok(0)
+    }
+
+    fn inc(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        if mode == AddressMode::Accumulator {
+            self.A = self.A.wrapping_add(1);
+            flags_zn!(self, self.A);
+        } else {
+            self.modify(mode, alu::inc)?;
+        }
+        Ok(0)
+    }
+
+    fn inx(&mut self, _: Op) -> Result<u8, Fault> {
+        self.X = self.X.wrapping_add(1);
+        flags_zn!(self, self.X);
+        Ok(0)
+    }
+
+    fn iny(&mut self, _: Op) -> Result<u8, Fault> {
+        self.Y = self.Y.wrapping_add(1);
+        flags_zn!(self, self.Y);
+        Ok(0)
+    }
+
+    fn dec(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        if mode == AddressMode::Accumulator {
+            self.A = self.A.wrapping_sub(1);
+            flags_zn!(self, self.A);
+        } else {
+            self.modify(mode, alu::dec)?;
+        }
+        Ok(0)
+    }
+
+    fn dex(&mut self, _: Op) -> Result<u8, Fault> {
+        self.X = self.X.wrapping_sub(1);
+        flags_zn!(self, self.X);
+        Ok(0)
+    }
+
+    fn dey(&mut self, _: Op) -> Result<u8, Fault> {
+        self.Y = self.Y.wrapping_sub(1);
+        flags_zn!(self, self.Y);
+        Ok(0)
+    }
+
+    fn asl(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.modify(mode, alu::asl)?;
+        Ok(0)
+    }
+
+    fn lsr(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.modify(mode, alu::lsr)?;
+        Ok(0)
+    }
+
+    fn rol(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let carry = self.C;
+        self.modify(mode, |data| alu::rol(data, carry))?;
+        Ok(0)
+    }
+
+    fn ror(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let carry = self.C;
+        self.modify(mode, |data| alu::ror(data, carry))?;
+        Ok(0)
+    }
+
+    fn jmp(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.PC = self.fetch_addr(mode)?;
+        Ok(0)
+    }
+
+    fn jsr(&mut self, _: Op) -> Result<u8, Fault> {
+        // The high byte of the target is fetched after the return address is pushed
+        let new_addr = self.fetch()?;
+        self.stack_read()?;
+        let [addr, page] = self.PC.to_le_bytes();
+        self.stack_push(page)?;
+        self.stack_push(addr)?;
+        let new_page = self.fetch()?;
+        self.PC = Address::Full(new_addr, new_page).into();
+        self.target(self.PC);
+        Ok(0)
+    }
+
+    fn rts(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_read()?;
+        let addr = self.stack_pop()?;
+        let page = self.stack_pop()?;
+        self.PC = Address::Full(addr, page).into();
+        self.read(self.PC)?;
+        self.PC = self.PC.wrapping_add(1);
+        Ok(0)
+    }
+
+    fn bcc(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, !self.C))
+    }
+
+    fn bcs(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, self.C))
+    }
+
+    fn beq(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, self.Z))
+    }
+
+    fn bmi(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, self.N))
+    }
+
+    fn bne(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, !self.Z))
+    }
+
+    fn bpl(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, !self.N))
+    }
+
+    fn bvc(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, !self.V))
+    }
+
+    fn bvs(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, self.V))
+    }
+
+    fn clc(&mut self, _: Op) -> Result<u8, Fault> {
+        self.C = false;
+        Ok(0)
+    }
+
+    fn cld(&mut self, _: Op) -> Result<u8, Fault> {
+        self.D = false;
+        Ok(0)
+    }
+
+    fn cli(&mut self, _: Op) -> Result<u8, Fault> {
+        self.I = false;
+        Ok(0)
+    }
+
+    fn clv(&mut self, _: Op) -> Result<u8, Fault> {
+        self.V = false;
+        Ok(0)
+    }
+
+    fn sec(&mut self, _: Op) -> Result<u8, Fault> {
+        self.C = true;
+        Ok(0)
+    }
+
+    fn sed(&mut self, _: Op) -> Result<u8, Fault> {
+        self.D = true;
+        Ok(0)
+    }
+
+    fn sei(&mut self, _: Op) -> Result<u8, Fault> {
+        self.I = true;
+        Ok(0)
+    }
+
+    fn brk(&mut self, _: Op) -> Result<u8, Fault> {
+        // The byte after BRK is padding that the handler can use as a signature
+        self.PC = self.PC.wrapping_add(1);
+        self.interrupt(IRQB, true)?;
+        Ok(0)
+    }
+
+    fn rti(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_read()?;
+
+        let status = self.stack_pop()?;
+        self.status_set(Status::from(status));
+
+        let addr = self.stack_pop()?;
+        let page = self.stack_pop()?;
+        self.PC = Address::Full(addr, page).into();
+        Ok(0)
+    }
+
+    fn nop(&mut self, Op(_, mode, cycles): Op) -> Result<u8, Fault> {
+        match mode {
+            AddressMode::Implicit => {}
+            AddressMode::Absolute if matches!(cycles, Cycles::Constant(8)) => {
+                // The 65C02 spends 4 more cycles reading the address on 0x5C
+                let addr = self.fetch_addr(AddressMode::Absolute)?;
+                for _ in 0..5 {
+                    self.read(addr)?;
+                }
+            }
+            mode => {
+                let addr = self.fetch_addr(mode)?;
+                self.read(addr)?;
+            }
+        }
+        Ok(0)
+    }
+
+    fn slo(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.A |= self.modify(mode, alu::asl)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn rla(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let carry = self.C;
+        self.A &= self.modify(mode, |data| alu::rol(data, carry))?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn sre(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.A ^= self.modify(mode, alu::lsr)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn rra(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let carry = self.C;
+        let data = self.modify(mode, |data| alu::ror(data, carry))?;
+        self.add(data);
+        Ok(0)
+    }
+
+    fn sax(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.write(addr, self.A & self.X)?;
+        Ok(0)
+    }
+
+    fn lax(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A = self.read(addr)?;
+        self.X = self.A;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn dcp(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let data = self.modify(mode, alu::dec)?;
+        self.alu(alu::compare(self.A, data));
+        Ok(0)
+    }
+
+    fn isc(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let data = self.modify(mode, alu::inc)?;
+        self.sub(data);
+        Ok(0)
+    }
+
+    fn anc(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A &= self.read(addr)?;
+        flags_zn!(self, self.A);
+        self.C = self.N;
+        Ok(0)
+    }
+
+    fn alr(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        let data = self.read(addr)?;
+        self.A = self.alu(alu::lsr(self.A & data));
+        Ok(0)
+    }
+
+    fn arr(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        let data = self.read(addr)?;
+        self.A = self.alu(alu::arr(self.A & data, self.C, self.D));
+        Ok(0)
+    }
+
+    fn sbx(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        let data = self.read(addr)?;
+        let out = alu::compare(self.A & self.X, data);
+        self.alu(out);
+        self.X = out.result.wrapping_sub(data);
+        Ok(0)
+    }
+
+    fn ane(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A = (self.A | ANE_MAGIC) & self.X & self.read(addr)?;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn lxa(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A = (self.A | ANE_MAGIC) & self.read(addr)?;
+        self.X = self.A;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn las(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.A = self.read(addr)? & self.SP;
+        self.X = self.A;
+        self.SP = self.A;
+        flags_zn!(self, self.A);
+        Ok(0)
+    }
+
+    fn sha(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.store_high(mode, self.A & self.X)?;
+        Ok(0)
+    }
+
+    fn shx(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.store_high(mode, self.X)?;
+        Ok(0)
+    }
+
+    fn shy(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.store_high(mode, self.Y)?;
+        Ok(0)
+    }
+
+    fn tas(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        self.SP = self.A & self.X;
+        self.store_high(mode, self.SP)?;
+        Ok(0)
+    }
+
+    fn jam(&mut self, _: Op) -> Result<u8, Fault> {
+        // Stay on the opcode, only a reset gets the processor going again
+        self.PC = self.PC.wrapping_sub(1);
+        self.run = RunState::Jammed(self.opcode.unwrap_or_default());
+        Ok(0)
+    }
+
+    fn bra(&mut self, _: Op) -> Result<u8, Fault> {
+        Ok(branch!(self, true))
+    }
+
+    fn phx(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_push(self.X)?;
+        Ok(0)
+    }
+
+    fn phy(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_push(self.Y)?;
+        Ok(0)
+    }
+
+    fn plx(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_read()?;
+        self.X = self.stack_pop()?;
+        flags_zn!(self, self.X);
+        Ok(0)
+    }
+
+    fn ply(&mut self, _: Op) -> Result<u8, Fault> {
+        self.stack_read()?;
+        self.Y = self.stack_pop()?;
+        flags_zn!(self, self.Y);
+        Ok(0)
+    }
+
+    fn stz(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let addr = self.fetch_addr(mode)?;
+        self.write(addr, 0)?;
+        Ok(0)
+    }
+
+    fn trb(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let a = self.A;
+        self.modify(mode, |data| alu::trb(a, data))?;
+        Ok(0)
+    }
+
+    fn tsb(&mut self, Op(_, mode, _): Op) -> Result<u8, Fault> {
+        let a = self.A;
+        self.modify(mode, |data| alu::tsb(a, data))?;
+        Ok(0)
+    }
+
+    fn wai(&mut self, _: Op) -> Result<u8, Fault> {
+        self.read(self.PC)?;
+        self.run = RunState::Waiting;
+        Ok(0)
+    }
+
+    fn stp(&mut self, _: Op) -> Result<u8, Fault> {
+        self.read(self.PC)?;
+        // Stay on the opcode, only a reset gets the processor going again
+        self.PC = self.PC.wrapping_sub(1);
+        self.run = RunState::Stopped;
+        Ok(0)
+    }
+
+    fn bbr(&mut self, Op(op, _, _): Op) -> Result<u8, Fault> {
+        let bit = op.bit();
+        let zero_page_addr = self.fetch()?;
+        let data = self.read(Address::Zero(zero_page_addr))?;
+        self.read(Address::Zero(zero_page_addr))?;
+        Ok(branch!(self, data & (1 << bit) == 0))
+    }
+
+    fn bbs(&mut self, Op(op, _, _): Op) -> Result<u8, Fault> {
+        let bit = op.bit();
+        let zero_page_addr = self.fetch()?;
+        let data = self.read(Address::Zero(zero_page_addr))?;
+        self.read(Address::Zero(zero_page_addr))?;
+        Ok(branch!(self, data & (1 << bit) > 0))
+    }
+
+    fn rmb(&mut self, Op(op, mode, _): Op) -> Result<u8, Fault> {
+        let bit = op.bit();
+        self.modify(mode, |data| alu::rmb(data, bit))?;
+        Ok(0)
+    }
+
+    fn smb(&mut self, Op(op, mode, _): Op) -> Result<u8, Fault> {
+        let bit = op.bit();
+        self.modify(mode, |data| alu::smb(data, bit))?;
+        Ok(0)
+    }
+}
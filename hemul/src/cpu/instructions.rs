@@ -1,8 +1,8 @@
-use super::CpuError;
-use crate::Byte;
+use super::{Cpu, IllegalOpCodes, Variant, handlers::Handler};
+use crate::{Byte, Device};
 
-/// The 6502 instruction set
-#[derive(Debug, Clone)]
+/// The 6502 instruction set, including the undocumented NMOS opcodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::doc_markdown)]
 pub enum OpCode {
     // Load/Store Operations
@@ -427,13 +427,231 @@ pub enum OpCode {
     /// The RTI instruction is used at the end of an interrupt processing routine. It pulls the
     /// processor flags from the stack followed by the program counter.
     Rti,
+
+    // Undocumented Operations
+    // The NMOS 6502 decodes every opcode, and the 105 that are missing from the datasheet combine
+    // parts of documented instructions. Plenty of software relies on the stable ones, see
+    // <https://www.masswerk.at/nowgobang/2021/6502-illegal-opcodes>
+    /// SLO - Shift Left then OR
+    /// ```text
+    /// M = M*2, A,Z,N = A|M
+    /// ```
+    /// Shifts memory left like ASL, then ORs the result into the accumulator.
+    Slo,
+
+    /// RLA - Rotate Left then AND
+    /// ```text
+    /// M = M*2+C, A,Z,N = A&M
+    /// ```
+    /// Rotates memory left like ROL, then ANDs the result into the accumulator.
+    Rla,
+
+    /// SRE - Shift Right then Exclusive OR
+    /// ```text
+    /// M = M/2, A,Z,N = A^M
+    /// ```
+    /// Shifts memory right like LSR, then exclusive ORs the result into the accumulator.
+    Sre,
+
+    /// RRA - Rotate Right then Add with Carry
+    /// ```text
+    /// M = M/2+C*128, A,Z,C,N,V = A+M+C
+    /// ```
+    /// Rotates memory right like ROR, then adds the result to the accumulator like ADC.
+    Rra,
+
+    /// SAX - Store A AND X
+    /// ```text
+    /// M = A&X
+    /// ```
+    /// Stores the bitwise AND of the accumulator and the X register without affecting any flags.
+    Sax,
+
+    /// LAX - Load Accumulator and X
+    /// ```text
+    /// A,X,Z,N = M
+    /// ```
+    /// Loads a byte of memory into both the accumulator and the X register.
+    Lax,
+
+    /// DCP - Decrement then Compare
+    /// ```text
+    /// M = M-1, Z,C,N = A-M
+    /// ```
+    /// Decrements memory like DEC, then compares the result with the accumulator like CMP.
+    Dcp,
+
+    /// ISC - Increment then Subtract with Carry
+    /// ```text
+    /// M = M+1, A,Z,C,N,V = A-M-(1-C)
+    /// ```
+    /// Increments memory like INC, then subtracts the result from the accumulator like SBC.
+    Isc,
+
+    /// ANC - AND then copy N to C
+    /// ```text
+    /// A,Z,N = A&M, C = N
+    /// ```
+    Anc,
+
+    /// ALR - AND then Logical Shift Right
+    /// ```text
+    /// A,C,Z,N = (A&M)/2
+    /// ```
+    Alr,
+
+    /// ARR - AND then Rotate Right
+    /// ```text
+    /// A,Z,N = (A&M)/2+C*128, C = A6, V = A6^A5
+    /// ```
+    /// The carry and overflow flags come from the rotated result, and in decimal mode the result
+    /// gets a BCD style fix up.
+    Arr,
+
+    /// SBX - Subtract from A AND X
+    /// ```text
+    /// X,Z,C,N = (A&X)-M
+    /// ```
+    /// Subtracts without borrow and sets the flags like CMP does.
+    Sbx,
+
+    /// ANE - AND X then AND immediate (also known as XAA)
+    /// ```text
+    /// A,Z,N = (A|$EE)&X&M
+    /// ```
+    /// Unstable on real hardware, the magic constant depends on the chip and its temperature.
+    Ane,
+
+    /// LXA - Load A and X through AND (also known as LAX immediate)
+    /// ```text
+    /// A,X,Z,N = (A|$EE)&M
+    /// ```
+    /// Unstable on real hardware, the magic constant depends on the chip and its temperature.
+    Lxa,
+
+    /// LAS - Load A, X and SP through AND
+    /// ```text
+    /// A,X,SP,Z,N = M&SP
+    /// ```
+    Las,
+
+    /// SHA - Store A AND X AND high byte (also known as AHX)
+    /// ```text
+    /// M = A&X&(H+1)
+    /// ```
+    /// H is the high byte of the base address. When indexing crosses a page the stored value also
+    /// replaces the high byte of the target address.
+    Sha,
+
+    /// SHX - Store X AND high byte
+    /// ```text
+    /// M = X&(H+1)
+    /// ```
+    Shx,
+
+    /// SHY - Store Y AND high byte
+    /// ```text
+    /// M = Y&(H+1)
+    /// ```
+    Shy,
+
+    /// TAS - Transfer A AND X to SP then store (also known as SHS)
+    /// ```text
+    /// SP = A&X, M = SP&(H+1)
+    /// ```
+    Tas,
+
+    /// JAM - Halt the processor
+    /// The processor locks up and only a reset brings it back.
+    Jam,
+
+    // 65C02 Operations
+    // The WDC 65C02 adds these instructions, including the Rockwell bit manipulation ones, on top
+    // of the documented NMOS instruction set.
+    /// BRA - Branch Always
+    /// Adds the relative displacement to the program counter unconditionally.
+    Bra,
+
+    /// PHX - Push X Register
+    /// Pushes a copy of the X register on to the stack.
+    Phx,
+
+    /// PHY - Push Y Register
+    /// Pushes a copy of the Y register on to the stack.
+    Phy,
+
+    /// PLX - Pull X Register
+    /// Pulls an 8 bit value from the stack and into the X register. The zero and negative flags
+    /// are set as appropriate.
+    Plx,
+
+    /// PLY - Pull Y Register
+    /// Pulls an 8 bit value from the stack and into the Y register. The zero and negative flags
+    /// are set as appropriate.
+    Ply,
+
+    /// STZ - Store Zero
+    /// ```text
+    /// M = 0
+    /// ```
+    Stz,
+
+    /// TRB - Test and Reset Bits
+    /// ```text
+    /// Z = A&M, M = M&~A
+    /// ```
+    /// Clears the bits of memory that are set in the accumulator.
+    Trb,
+
+    /// TSB - Test and Set Bits
+    /// ```text
+    /// Z = A&M, M = M|A
+    /// ```
+    /// Sets the bits of memory that are set in the accumulator.
+    Tsb,
+
+    /// WAI - Wait for Interrupt
+    /// Stops the processor until an interrupt arrives, which lets it respond with no latency.
+    Wai,
+
+    /// STP - Stop the Processor
+    /// Stops the processor until it is reset.
+    Stp,
+
+    /// BBR - Branch on Bit Reset
+    /// Branches if the given bit of a zero page location is clear.
+    Bbr(u8),
+
+    /// BBS - Branch on Bit Set
+    /// Branches if the given bit of a zero page location is set.
+    Bbs(u8),
+
+    /// RMB - Reset Memory Bit
+    /// Clears the given bit of a zero page location.
+    Rmb(u8),
+
+    /// SMB - Set Memory Bit
+    /// Sets the given bit of a zero page location.
+    Smb(u8),
 }
 
-/// The 6502 processor provides several ways in which memory locations can be addressed. Some
-/// instructions support several different modes while others may only support one. In addition the
-/// two index registers can not always be used interchangeably. This lack of orthogonality in the
-/// instruction set is one of the features that makes the 6502 trickier to program well.
-#[derive(Debug, Clone)]
+impl OpCode {
+    /// Bit that BBR, BBS, RMB and SMB work on, 0 for the other instructions
+    pub(super) fn bit(self) -> u8 {
+        match self {
+            Self::Bbr(bit) | Self::Bbs(bit) | Self::Rmb(bit) | Self::Smb(bit) => bit,
+            _ => 0,
+        }
+    }
+}
+
+/// Ways in which an instruction addresses memory.
+///
+/// Some instructions support several different modes while others may only support one. In
+/// addition the two index registers can not always be used interchangeably. This lack of
+/// orthogonality in the instruction set is one of the features that makes the 6502 trickier to
+/// program well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressMode {
     /// For many 6502 instructions the source and destination of the information to be manipulated
     /// is implied directly by the function of the instruction itself and no further operand needs
@@ -581,10 +799,58 @@ pub enum AddressMode {
     /// STA (DST),Y     ;Store accumulator indirectly into memory
     /// ```
     IndirectIndexed,
+
+    /// Zero page indirect addressing is added by the 65C02. The instruction contains the zero page
+    /// location of the least significant byte of the 16 bit target address, without any index.
+    ///
+    /// ```asm
+    /// LDA ($40)       ;Load a byte indirectly from memory
+    /// ```
+    ZeroPageIndirect,
+
+    /// Absolute indexed indirect addressing is added by the 65C02 for jump tables. The X register
+    /// is added to the 16 bit address in the instruction to give the location of the target
+    /// address.
+    ///
+    /// ```asm
+    /// JMP ($1234,X)   ;Jump through a table of addresses
+    /// ```
+    AbsoluteIndexedIndirect,
+
+    /// Zero page relative addressing is used by the 65C02 bit branches. The instruction contains a
+    /// zero page location to test followed by a relative branch displacement.
+    ///
+    /// ```asm
+    /// BBR0 $40,LABEL  ;Branch if bit 0 of $40 is clear
+    /// ```
+    ZeroPageRelative,
+}
+
+impl AddressMode {
+    /// Number of operand bytes that follow the op code
+    pub fn operands(self) -> u8 {
+        match self {
+            Self::Implicit | Self::Accumulator => 0,
+            Self::Immediate
+            | Self::ZeroPage
+            | Self::ZeroPageX
+            | Self::ZeroPageY
+            | Self::Relative
+            | Self::IndexedIndirect
+            | Self::IndirectIndexed
+            | Self::ZeroPageIndirect => 1,
+            Self::Absolute
+            | Self::AbsoluteX
+            | Self::AbsoluteY
+            | Self::Indirect
+            | Self::AbsoluteIndexedIndirect
+            | Self::ZeroPageRelative => 2,
+        }
+    }
 }
 
 /// Denotes how many cycles a particular instruction takes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub enum Cycles {
     /// The instruction always takes constant time
     Constant(u8),
@@ -597,7 +863,7 @@ pub enum Cycles {
 }
 
 /// Container for instruction, address mode and number of cycles used for a given combo
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub struct Op(pub OpCode, pub AddressMode, pub Cycles);
 
 impl std::fmt::Debug for Op {
@@ -614,6 +880,16 @@ impl std::fmt::Debug for Op {
 }
 
 macro_rules! op {
+    ($op_code:ident, $bit:expr, $address_mode:ident, $cycles: literal) => {
+        Op(
+            OpCode::$op_code($bit),
+            AddressMode::$address_mode,
+            Cycles::Constant($cycles),
+        )
+    };
+    ($op_code:ident, $bit:expr, $address_mode:ident, $cycles: expr) => {
+        Op(OpCode::$op_code($bit), AddressMode::$address_mode, $cycles)
+    };
     ($op_code:ident, $address_mode:ident, $cycles: literal) => {
         Op(
             OpCode::$op_code,
@@ -627,7 +903,8 @@ macro_rules! op {
 }
 
 impl TryFrom<Byte> for Op {
-    type Error = CpuError;
+    /// The op code that has no instruction
+    type Error = Byte;
 
     #[allow(clippy::too_many_lines)]
     fn try_from(value: Byte) -> Result<Self, Self::Error> {
@@ -789,7 +1066,7 @@ impl TryFrom<Byte> for Op {
             0x4C => op!(Jmp, Absolute, 3),
             0x6C => op!(Jmp, Indirect, 5),
 
-            0x20 => op!(Jsr, Implicit, 6),
+            0x20 => op!(Jsr, Absolute, 6),
             0x60 => op!(Rts, Implicit, 6),
 
             // All these have +1 if branch succeeds, +2 if to a new page
@@ -816,8 +1093,227 @@ impl TryFrom<Byte> for Op {
             0x40 => op!(Rti, Implicit, 6),
 
             _ => {
-                return Err(CpuError::BadOpCode(value));
+                return Err(value);
             }
         })
     }
 }
+
+/// Every op code decoded up front for a variant and undocumented op code policy, with the
+/// handler that runs it, so running an instruction only has to look it up
+pub(super) struct Decoder<T: Device>([Option<(Op, Handler<T>)>; 256]);
+
+impl<T> Decoder<T>
+where
+    T: Device,
+{
+    pub fn new(variant: Variant, illegal: IllegalOpCodes) -> Self {
+        Self(std::array::from_fn(|i| {
+            Byte::try_from(i)
+                .ok()
+                .and_then(|value| Op::decode(variant, illegal, value))
+                .map(|op| (op, Cpu::handler(op.0)))
+        }))
+    }
+
+    /// The op that an op code decodes to and its handler, `None` if the Cpu fails on it
+    pub fn get(&self, value: Byte) -> Option<(Op, Handler<T>)> {
+        self.0[usize::from(value)]
+    }
+}
+
+impl Op {
+    /// Decode an op code for a variant and undocumented op code policy, `None` if the Cpu fails
+    /// on it
+    fn decode(variant: Variant, illegal: IllegalOpCodes, value: Byte) -> Option<Self> {
+        if variant == Variant::Wdc65C02 {
+            // Opcodes without an instruction decode as NOPs, which the policy can turn into errors
+            return match Self::wdc65c02(value) {
+                Self(OpCode::Nop, _, _) if value != 0xEA && illegal == IllegalOpCodes::Error => {
+                    None
+                }
+                op => Some(op),
+            };
+        }
+        Self::try_from(value).ok().or_else(|| match illegal {
+            IllegalOpCodes::Emulate => Self::undocumented(value),
+            IllegalOpCodes::Nop => Self::undocumented(value)
+                .map(|Self(_, mode, cycles)| Self(OpCode::Nop, mode, cycles)),
+            IllegalOpCodes::Error => None,
+        })
+    }
+
+    /// Decode one of the 105 opcodes that are missing from the NMOS 6502 datasheet
+    #[allow(clippy::too_many_lines)]
+    pub fn undocumented(value: Byte) -> Option<Self> {
+        Some(match value {
+            0x07 => op!(Slo, ZeroPage, 5),
+            0x17 => op!(Slo, ZeroPageX, 6),
+            0x0F => op!(Slo, Absolute, 6),
+            0x1F => op!(Slo, AbsoluteX, 7),
+            0x1B => op!(Slo, AbsoluteY, 7),
+            0x03 => op!(Slo, IndexedIndirect, 8),
+            0x13 => op!(Slo, IndirectIndexed, 8),
+
+            0x27 => op!(Rla, ZeroPage, 5),
+            0x37 => op!(Rla, ZeroPageX, 6),
+            0x2F => op!(Rla, Absolute, 6),
+            0x3F => op!(Rla, AbsoluteX, 7),
+            0x3B => op!(Rla, AbsoluteY, 7),
+            0x23 => op!(Rla, IndexedIndirect, 8),
+            0x33 => op!(Rla, IndirectIndexed, 8),
+
+            0x47 => op!(Sre, ZeroPage, 5),
+            0x57 => op!(Sre, ZeroPageX, 6),
+            0x4F => op!(Sre, Absolute, 6),
+            0x5F => op!(Sre, AbsoluteX, 7),
+            0x5B => op!(Sre, AbsoluteY, 7),
+            0x43 => op!(Sre, IndexedIndirect, 8),
+            0x53 => op!(Sre, IndirectIndexed, 8),
+
+            0x67 => op!(Rra, ZeroPage, 5),
+            0x77 => op!(Rra, ZeroPageX, 6),
+            0x6F => op!(Rra, Absolute, 6),
+            0x7F => op!(Rra, AbsoluteX, 7),
+            0x7B => op!(Rra, AbsoluteY, 7),
+            0x63 => op!(Rra, IndexedIndirect, 8),
+            0x73 => op!(Rra, IndirectIndexed, 8),
+
+            0x87 => op!(Sax, ZeroPage, 3),
+            0x97 => op!(Sax, ZeroPageY, 4),
+            0x8F => op!(Sax, Absolute, 4),
+            0x83 => op!(Sax, IndexedIndirect, 6),
+
+            0xA7 => op!(Lax, ZeroPage, 3),
+            0xB7 => op!(Lax, ZeroPageY, 4),
+            0xAF => op!(Lax, Absolute, 4),
+            0xBF => op!(Lax, AbsoluteY, Cycles::Page(4)), // +1 if page is crossed
+            0xA3 => op!(Lax, IndexedIndirect, 6),
+            0xB3 => op!(Lax, IndirectIndexed, Cycles::Page(5)), // +1 if page is crossed
+
+            0xC7 => op!(Dcp, ZeroPage, 5),
+            0xD7 => op!(Dcp, ZeroPageX, 6),
+            0xCF => op!(Dcp, Absolute, 6),
+            0xDF => op!(Dcp, AbsoluteX, 7),
+            0xDB => op!(Dcp, AbsoluteY, 7),
+            0xC3 => op!(Dcp, IndexedIndirect, 8),
+            0xD3 => op!(Dcp, IndirectIndexed, 8),
+
+            0xE7 => op!(Isc, ZeroPage, 5),
+            0xF7 => op!(Isc, ZeroPageX, 6),
+            0xEF => op!(Isc, Absolute, 6),
+            0xFF => op!(Isc, AbsoluteX, 7),
+            0xFB => op!(Isc, AbsoluteY, 7),
+            0xE3 => op!(Isc, IndexedIndirect, 8),
+            0xF3 => op!(Isc, IndirectIndexed, 8),
+
+            0x0B | 0x2B => op!(Anc, Immediate, 2),
+            0x4B => op!(Alr, Immediate, 2),
+            0x6B => op!(Arr, Immediate, 2),
+            0xCB => op!(Sbx, Immediate, 2),
+            0x8B => op!(Ane, Immediate, 2),
+            0xAB => op!(Lxa, Immediate, 2),
+            0xEB => op!(Sbc, Immediate, 2),
+
+            0xBB => op!(Las, AbsoluteY, Cycles::Page(4)), // +1 if page is crossed
+            0x93 => op!(Sha, IndirectIndexed, 6),
+            0x9F => op!(Sha, AbsoluteY, 5),
+            0x9E => op!(Shx, AbsoluteY, 5),
+            0x9C => op!(Shy, AbsoluteX, 5),
+            0x9B => op!(Tas, AbsoluteY, 5),
+
+            0x1A | 0x3A | 0x5A | 0x7A | 0xDA | 0xFA => op!(Nop, Implicit, 2),
+            0x80 | 0x82 | 0x89 | 0xC2 | 0xE2 => op!(Nop, Immediate, 2),
+            0x04 | 0x44 | 0x64 => op!(Nop, ZeroPage, 3),
+            0x14 | 0x34 | 0x54 | 0x74 | 0xD4 | 0xF4 => op!(Nop, ZeroPageX, 4),
+            0x0C => op!(Nop, Absolute, 4),
+            // +1 if page is crossed
+            0x1C | 0x3C | 0x5C | 0x7C | 0xDC | 0xFC => op!(Nop, AbsoluteX, Cycles::Page(4)),
+
+            0x02 | 0x12 | 0x22 | 0x32 | 0x42 | 0x52 | 0x62 | 0x72 | 0x92 | 0xB2 | 0xD2 | 0xF2 => {
+                op!(Jam, Implicit, 2)
+            }
+
+            _ => return None,
+        })
+    }
+
+    /// Decode an opcode for the WDC 65C02. Every opcode is defined, the ones without an
+    /// instruction are NOPs of various lengths and cycle counts.
+    #[allow(clippy::too_many_lines)]
+    pub fn wdc65c02(value: Byte) -> Self {
+        match value {
+            0x80 => op!(Bra, Relative, Cycles::Branch(2)), // +1 if to a new page
+
+            0xDA => op!(Phx, Implicit, 3),
+            0x5A => op!(Phy, Implicit, 3),
+            0xFA => op!(Plx, Implicit, 4),
+            0x7A => op!(Ply, Implicit, 4),
+
+            0x64 => op!(Stz, ZeroPage, 3),
+            0x74 => op!(Stz, ZeroPageX, 4),
+            0x9C => op!(Stz, Absolute, 4),
+            0x9E => op!(Stz, AbsoluteX, 5),
+
+            0x14 => op!(Trb, ZeroPage, 5),
+            0x1C => op!(Trb, Absolute, 6),
+            0x04 => op!(Tsb, ZeroPage, 5),
+            0x0C => op!(Tsb, Absolute, 6),
+
+            0x1A => op!(Inc, Accumulator, 2),
+            0x3A => op!(Dec, Accumulator, 2),
+
+            0x89 => op!(Bit, Immediate, 2),
+            0x34 => op!(Bit, ZeroPageX, 4),
+            0x3C => op!(Bit, AbsoluteX, Cycles::Page(4)), // +1 if page is crossed
+
+            0x12 => op!(Ora, ZeroPageIndirect, 5),
+            0x32 => op!(And, ZeroPageIndirect, 5),
+            0x52 => op!(Eor, ZeroPageIndirect, 5),
+            0x72 => op!(Adc, ZeroPageIndirect, 5),
+            0x92 => op!(Sta, ZeroPageIndirect, 5),
+            0xB2 => op!(Lda, ZeroPageIndirect, 5),
+            0xD2 => op!(Cmp, ZeroPageIndirect, 5),
+            0xF2 => op!(Sbc, ZeroPageIndirect, 5),
+
+            0x6C => op!(Jmp, Indirect, 6),
+            0x7C => op!(Jmp, AbsoluteIndexedIndirect, 6),
+
+            // The shifts and rotates save a cycle when indexing stays on the page
+            0x1E => op!(Asl, AbsoluteX, Cycles::Page(6)),
+            0x3E => op!(Rol, AbsoluteX, Cycles::Page(6)),
+            0x5E => op!(Lsr, AbsoluteX, Cycles::Page(6)),
+            0x7E => op!(Ror, AbsoluteX, Cycles::Page(6)),
+
+            0xCB => op!(Wai, Implicit, 3),
+            0xDB => op!(Stp, Implicit, 3),
+
+            // +1 if branch succeeds, +2 if to a new page
+            0x0F | 0x1F | 0x2F | 0x3F | 0x4F | 0x5F | 0x6F | 0x7F => {
+                op!(Bbr, value >> 4, ZeroPageRelative, Cycles::Branch(5))
+            }
+            0x8F | 0x9F | 0xAF | 0xBF | 0xCF | 0xDF | 0xEF | 0xFF => {
+                op!(
+                    Bbs,
+                    (value >> 4) & 0b111,
+                    ZeroPageRelative,
+                    Cycles::Branch(5)
+                )
+            }
+            0x07 | 0x17 | 0x27 | 0x37 | 0x47 | 0x57 | 0x67 | 0x77 => {
+                op!(Rmb, value >> 4, ZeroPage, 5)
+            }
+            0x87 | 0x97 | 0xA7 | 0xB7 | 0xC7 | 0xD7 | 0xE7 | 0xF7 => {
+                op!(Smb, (value >> 4) & 0b111, ZeroPage, 5)
+            }
+
+            0x02 | 0x22 | 0x42 | 0x62 | 0x82 | 0xC2 | 0xE2 => op!(Nop, Immediate, 2),
+            0x44 => op!(Nop, ZeroPage, 3),
+            0x54 | 0xD4 | 0xF4 => op!(Nop, ZeroPageX, 4),
+            0xDC | 0xFC => op!(Nop, Absolute, 4),
+            0x5C => op!(Nop, Absolute, 8),
+
+            _ => Self::try_from(value).unwrap_or(op!(Nop, Implicit, 1)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use super::{IRQB, NMIB, PFlag};
+use crate::{Word, line::Line};
+
+/// Interrupt lines as seen at the start of a cycle
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sample {
+    /// IRQ is asserted
+    pub irq: bool,
+    /// An NMI edge is waiting to be taken
+    pub nmi: bool,
+}
+
+/// The IRQ and NMI lines and the state of polling them
+#[derive(Debug, Default)]
+pub struct Interrupts {
+    /// Level triggered interrupt request
+    pub irq: Line,
+    /// Edge triggered non-maskable interrupt
+    pub nmi: Line,
+
+    /// NMI as sampled on the previous cycle, to detect edges
+    nmi_prev: bool,
+    /// Set on an NMI edge until the NMI is taken
+    nmi_latched: bool,
+
+    /// Cycle of the current instruction, counting from 1
+    cycle: u8,
+    /// Lines as sampled at the start of the current cycle
+    now: Sample,
+    /// Lines as sampled at the start of the second cycle of the current instruction
+    second: Sample,
+
+    /// Interrupt disable flag that the current instruction polls with
+    pub poll_i: PFlag,
+    /// Set when the current instruction polls on its second cycle instead of its last one, like
+    /// a taken branch that stays on the page
+    pub poll_second: bool,
+
+    /// Interrupt vector to take after the current instruction
+    pub pending: Option<Word>,
+    /// Whether an NMI took over the vector of the current BRK or IRQ
+    hijack: Option<bool>,
+}
+
+impl Interrupts {
+    /// Sample the lines at the start of a cycle, latching an NMI on a falling edge
+    pub fn sample(&mut self, first: bool) {
+        let nmi = self.nmi.asserted();
+        if nmi && !self.nmi_prev {
+            self.nmi_latched = true;
+        }
+        self.nmi_prev = nmi;
+        self.now = Sample {
+            irq: self.irq.asserted(),
+            nmi: self.nmi_latched,
+        };
+
+        self.cycle = if first {
+            1
+        } else {
+            self.cycle.saturating_add(1)
+        };
+        if self.cycle <= 2 {
+            self.second = self.now;
+        }
+    }
+
+    /// Decide if an interrupt is taken after the instruction that ends on this cycle
+    pub fn poll(&mut self) {
+        let sample = if self.poll_second {
+            self.second
+        } else {
+            self.now
+        };
+        self.take(sample, self.poll_i);
+    }
+
+    /// Whether a processor that waits for an interrupt wakes up on this cycle, which even a
+    /// masked IRQ does. It then takes the interrupt if it is not masked.
+    pub fn wake(&mut self, i: PFlag) -> bool {
+        if self.now.irq || self.now.nmi {
+            self.take(self.now, i);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Make an interrupt pending if the lines sampled call for it
+    fn take(&mut self, sample: Sample, i: PFlag) {
+        if sample.nmi && self.nmi_latched {
+            self.nmi_latched = false;
+            self.pending = Some(NMIB);
+        } else if sample.irq && !i && self.pending.is_none() {
+            self.pending = Some(IRQB);
+        }
+    }
+
+    /// Whether an NMI takes over the vector of the BRK or IRQ that is running. The answer stays
+    /// the same until [`Interrupts::done`], so stepped instructions can ask again.
+    pub fn hijacked(&mut self) -> bool {
+        if let Some(hijack) = self.hijack {
+            return hijack;
+        }
+        let hijack = self.nmi_latched;
+        self.nmi_latched = false;
+        self.hijack = Some(hijack);
+        hijack
+    }
+
+    /// Forget the state of the instruction that just finished
+    pub fn done(&mut self) {
+        self.hijack = None;
+    }
+
+    /// Forget everything but the lines
+    pub fn reset(&mut self) {
+        self.nmi_latched = false;
+        self.pending = None;
+        self.hijack = None;
+        self.poll_i = false;
+        self.poll_second = false;
+    }
+}
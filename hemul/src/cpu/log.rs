@@ -0,0 +1,70 @@
+//! Structured events of the `tracing` feature, all with the `hemul::cpu` target. Instructions are
+//! logged at trace level, interrupt and reset sequences at debug level and errors at error level.
+
+use std::ops::RangeInclusive;
+
+use super::{AddressMode, Cpu, CpuError, OpCode, RESB};
+use crate::{Byte, Device, Word};
+
+impl<T> Cpu<T>
+where
+    T: Device,
+{
+    /// Only log instructions with their op code in the range, which is all of memory by default
+    pub fn log_range_set(&mut self, range: RangeInclusive<Word>) {
+        self.log_range = range;
+    }
+
+    /// Log the instruction that just ran, with the registers it left behind
+    pub(super) fn log_instruction(&self, op: OpCode, mode: AddressMode) {
+        if !self.log_range.contains(&self.op_pc) {
+            return;
+        }
+        tracing::trace!(
+            target: "hemul::cpu",
+            pc = self.op_pc,
+            opcode = self.opcode.unwrap_or_default(),
+            ?op,
+            ?mode,
+            a = self.A,
+            x = self.X,
+            y = self.Y,
+            sp = self.SP,
+            p = Byte::from(self.status_get()),
+            "instruction"
+        );
+    }
+
+    /// Log an interrupt or reset sequence that just ran, `pc` is where it was taken
+    pub(super) fn log_interrupt(&self, vector: Word) {
+        tracing::debug!(
+            target: "hemul::cpu",
+            pc = self.op_pc,
+            vector,
+            handler = self.PC,
+            a = self.A,
+            x = self.X,
+            y = self.Y,
+            sp = self.SP,
+            p = Byte::from(self.status_get()),
+            "{}",
+            if vector == RESB { "reset" } else { "interrupt" }
+        );
+    }
+
+    /// Log an instruction that failed
+    pub(super) fn log_error(&self, error: &CpuError) {
+        tracing::error!(
+            target: "hemul::cpu",
+            pc = self.op_pc,
+            opcode = self.opcode,
+            a = self.A,
+            x = self.X,
+            y = self.Y,
+            sp = self.SP,
+            p = Byte::from(self.status_get()),
+            %error,
+            "instruction failed"
+        );
+    }
+}
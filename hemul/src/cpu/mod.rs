@@ -1,45 +1,156 @@
-use std::error::Error;
-
 use self::{
     address::Address,
-    instructions::{AddressMode, Cycles, OpCode},
+    instructions::Cycles,
+    interrupt::Interrupts,
+    pins::Pins,
+    record::{Access, Record, Trace},
+    status::Status,
+};
+use crate::{
+    Byte, Device, Interruptible, Resettable, RunState, Tickable, Word, bus::BusError, line::Line,
+    power::PowerOn,
 };
-use crate::{Addressable, Byte, Interruptible, Resettable, Tickable, Word};
-use instructions::Op;
+use handlers::Handler;
+pub use instructions::{AddressMode, OpCode};
+use instructions::{Decoder, Op};
 use thiserror::Error;
 
 pub(crate) mod address;
+pub mod alu;
+mod handlers;
 mod instructions;
+pub(crate) mod interrupt;
+#[cfg(feature = "tracing")]
+mod log;
+mod pins;
+pub mod record;
 pub mod snapshot;
+pub mod status;
 
 pub(crate) type PFlag = bool;
 
 pub const SP_PAGE: Byte = 0x01;
-pub const SP_ADDR: Byte = 0xFF;
 pub const NMIB: Word = 0xFFFA; // + 0xFFFB
 pub const RESB: Word = 0xFFFC; // + 0xFFFD
 pub const IRQB: Word = 0xFFFE; // + 0xFFFF
 
-// Status register flag bits
-const C_FLAG: Byte = 0b0100_0000; // Carry
-const Z_FLAG: Byte = 0b0010_0000; // Zero
-const I_FLAG: Byte = 0b0001_0000; // Interrupt Disable
-const D_FLAG: Byte = 0b0000_1000; // Decimal Mode
-const B_FLAG: Byte = 0b0000_0100; // Break Command
-const V_FLAG: Byte = 0b0000_0010; // Overflow
-const N_FLAG: Byte = 0b0000_0001; // Negative
+/// The magic constant that the unstable ANE and LXA opcodes OR into the accumulator
+const ANE_MAGIC: Byte = 0xEE;
 
-/// Cpu mode
+/// How the Cpu spends clock cycles, set with [`Cpu::with_mode`] or [`Cpu::mode_set`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     /// Each clock cycle executes a single instruction
+    #[default]
     Fast,
 
     /// Each instruction takes as many clock cycles as the original 6502 used
-    Original(u8),
+    Original,
+
+    /// Each clock cycle does the one bus access that the original 6502 did on that cycle
+    Stepped,
+}
+
+impl std::str::FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fast" => Ok(Self::Fast),
+            "original" => Ok(Self::Original),
+            "stepped" => Ok(Self::Stepped),
+            _ => Err(format!(
+                "invalid mode '{s}', expected fast, original or stepped"
+            )),
+        }
+    }
+}
+
+/// The processor that the Cpu models
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The original NMOS 6502
+    Nmos6502,
+
+    /// The WDC 65C02, with the CMOS instructions, addressing modes and bug fixes
+    Wdc65C02,
+}
+
+/// Bus accesses of an instruction that runs one cycle at a time. Every tick runs the instruction
+/// again from the start, reusing the accesses of earlier cycles, until it does one new access.
+#[derive(Default)]
+struct Steps {
+    /// Set while a tick in stepped mode runs the instruction
+    active: bool,
+    /// Accesses done by the cycles that already ran
+    done: Vec<Access>,
+    /// How many of those the current run has reused
+    replayed: usize,
+    /// How many accesses were done before the current tick
+    before: usize,
+}
+
+impl Steps {
+    /// Get an access that an earlier cycle already did. Fails with [`Fault::Suspended`] if the
+    /// current cycle has done its access.
+    fn replay(&mut self) -> Result<Option<Access>, Fault> {
+        if !self.active {
+            return Ok(None);
+        }
+        if let Some(&access) = self.done.get(self.replayed) {
+            self.replayed += 1;
+            return Ok(Some(access));
+        }
+        if self.done.len() > self.before {
+            return Err(Fault::Suspended);
+        }
+        Ok(None)
+    }
+
+    /// Keep a new access for the next cycles
+    fn record(&mut self, access: Access) {
+        if self.active {
+            self.done.push(access);
+            self.replayed += 1;
+        }
+    }
+}
+
+/// Registers and flags, saved so that a stepped instruction can be run again
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    PC: Word,
+    SP: Byte,
+    A: Byte,
+    X: Byte,
+    Y: Byte,
+    C: PFlag,
+    Z: PFlag,
+    I: PFlag,
+    D: PFlag,
+    V: PFlag,
+    N: PFlag,
+    run: RunState,
+}
+
+/// How the Cpu handles the 105 opcodes that are missing from the NMOS 6502 datasheet. The 65C02
+/// runs its reserved opcodes as NOPs under both [`IllegalOpCodes::Emulate`] and
+/// [`IllegalOpCodes::Nop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalOpCodes {
+    /// Execute them like the NMOS 6502 does
+    Emulate,
+
+    /// Skip over them like a NOP with the same address mode and cycle count
+    Nop,
+
+    /// Fail with [`CpuError::BadOpCode`], on the 65C02 as well
+    Error,
 }
 
 #[allow(non_snake_case, dead_code)]
-pub struct Cpu<T: Addressable> {
+pub struct Cpu<T: Device> {
     addr: T,
 
     /// Program Counter
@@ -62,35 +173,123 @@ pub struct Cpu<T: Addressable> {
     I: PFlag,
     /// Decimal Mode
     D: PFlag,
-    /// Break Command
-    B: PFlag,
     /// Overflow Flag
     V: PFlag,
     /// Negative Flag
     N: PFlag,
 
+    /// The processor being modelled
+    variant: Variant,
+
     /// The mode the Cpu runs in
     mode: Mode,
+
+    /// Cycles left of the instruction that runs in original mode
+    burn: u8,
+
+    /// Set while the instruction that runs is not an interrupt or reset sequence
+    retiring: bool,
+
+    /// Whether the Cpu runs, waits for an interrupt or is halted
+    run: RunState,
+
+    /// Set when indexing in the current instruction crossed a page
+    page_crossed: bool,
+
+    /// Set when the current instruction only takes a cycle to fix the high byte of an indexed
+    /// address if indexing crosses a page
+    page_penalty: bool,
+
+    /// Bus accesses of the instruction being stepped through
+    steps: Steps,
+
+    /// IRQ and NMI lines
+    interrupts: Interrupts,
+
+    /// RDY, SO and SYNC pins
+    pins: Pins,
+
+    /// Clock cycles used since reset
+    cycles: u64,
+
+    /// Instructions retired since reset
+    instructions: u64,
+
+    /// Op codes decoded for the variant and how undocumented opcodes are handled
+    decoder: Decoder<T>,
+
+    /// What the current instruction did, kept while [`Cpu::step`] runs
+    trace: Option<Trace>,
+
+    /// Address of the current instruction, or of the interrupt sequence
+    op_pc: Word,
+
+    /// Op code of the current instruction, once it has been fetched
+    opcode: Option<Byte>,
+
+    /// Addresses of the instructions that are logged
+    #[cfg(feature = "tracing")]
+    log_range: std::ops::RangeInclusive<Word>,
 }
 
+/// Why the Cpu failed, with the address and op code of the instruction that failed
 #[allow(clippy::module_name_repetitions)]
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum CpuError {
-    #[error("invalid opcode `{0:#04x}`")]
-    BadOpCode(Byte),
+    #[error("invalid opcode `{opcode:#04x}` at `{pc:#06x}`")]
+    BadOpCode { pc: Word, opcode: Byte },
+
+    #[error("invalid address mode for opcode `{opcode:#04x}` at `{pc:#06x}`")]
+    InvalidAddressMode { pc: Word, opcode: Byte },
+
+    /// The op code is missing if the fault hit while fetching it or in an interrupt sequence
+    #[error("bus fault in instruction at `{pc:#06x}`: {source}")]
+    Bus {
+        pc: Word,
+        opcode: Option<Byte>,
+        source: BusError,
+    },
+
+    #[error("endless loop, no NOP reached in {0} ticks")]
+    EndlessLoop(usize),
+}
+
+/// Why an instruction stopped before it was done
+#[derive(Debug)]
+enum Fault {
+    /// Stepped mode did the bus access of this cycle, the rest runs on the next ticks
+    Suspended,
 
-    #[error("memory location out of bounds `{0:#06x}`")]
-    OutOfBounds(Word),
+    /// The instruction failed
+    Cpu(CpuError),
+}
 
-    #[error("invalid address mode")]
-    InvalidAddressMode,
+impl From<CpuError> for Fault {
+    fn from(e: CpuError) -> Self {
+        Self::Cpu(e)
+    }
 }
 
 impl<T> Cpu<T>
 where
-    T: Addressable,
+    T: Device,
 {
     pub fn new(addr: T) -> Self {
+        Self::with_variant(addr, Variant::Nmos6502)
+    }
+
+    /// NMOS 6502 that runs in the given mode
+    pub fn with_mode(addr: T, mode: Mode) -> Self {
+        Self::with_variant_and_mode(addr, Variant::Nmos6502, mode)
+    }
+
+    /// Processor of the given variant that runs in fast mode
+    pub fn with_variant(addr: T, variant: Variant) -> Self {
+        Self::with_variant_and_mode(addr, variant, Mode::Fast)
+    }
+
+    /// Processor of the given variant that runs in the given mode
+    pub fn with_variant_and_mode(addr: T, variant: Variant, mode: Mode) -> Self {
         Self {
             addr,
 
@@ -105,233 +304,747 @@ where
             Z: false,
             I: false,
             D: false,
-            B: false,
             V: false,
             N: false,
 
-            mode: Mode::Fast,
+            variant,
+
+            mode,
+
+            burn: 0,
+
+            retiring: false,
+
+            run: RunState::Running,
+
+            page_crossed: false,
+
+            page_penalty: false,
+
+            steps: Steps::default(),
+
+            interrupts: Interrupts::default(),
+
+            pins: Pins::default(),
+
+            cycles: 0,
+
+            instructions: 0,
+
+            decoder: Decoder::new(variant, IllegalOpCodes::Emulate),
+
+            trace: None,
+
+            op_pc: 0,
+
+            opcode: None,
+
+            #[cfg(feature = "tracing")]
+            log_range: 0..=Word::MAX,
         }
     }
 
     /// Read byte from address
-    fn read(&self, addr: impl Into<Word>) -> Result<Byte, CpuError> {
+    fn read(&mut self, addr: impl Into<Word>) -> Result<Byte, Fault> {
+        if let Some(access) = self.steps.replay()? {
+            return Ok(access.data);
+        }
+        // RDY low holds a stepped instruction on its next read
+        if self.steps.active && self.pins.rdy.asserted() {
+            return Err(Fault::Suspended);
+        }
+        let addr = addr.into();
+        if !self.addr.inside_bounds(addr) {
+            return Err(self.bus_fault(BusError::OutOfBounds(addr)));
+        }
+        let data = self.addr.read(addr);
+        self.note(Access {
+            addr,
+            data,
+            write: false,
+        });
+        Ok(data)
+    }
+
+    /// Keep a new bus access for stepped mode and [`Cpu::step`]
+    fn note(&mut self, access: Access) {
+        self.steps.record(access);
+        if let Some(trace) = &mut self.trace {
+            trace.accesses.push(access);
+        }
+    }
+
+    /// Read byte from address without it being a bus access, so without side effects
+    fn peek(&self, addr: impl Into<Word>) -> Result<Byte, BusError> {
         let addr = addr.into();
         if self.addr.inside_bounds(addr) {
-            Ok(self.addr[addr])
+            Ok(self.addr.peek(addr))
         } else {
-            Err(CpuError::OutOfBounds(addr))
+            Err(BusError::OutOfBounds(addr))
         }
     }
 
-    /// Read a word from address and address + 1
-    fn read_word(&self, addr: impl Into<Word>) -> Result<Word, CpuError> {
+    /// Failed bus access in the current instruction
+    fn bus_fault(&self, source: BusError) -> Fault {
+        Fault::Cpu(CpuError::Bus {
+            pc: self.op_pc,
+            opcode: self.opcode,
+            source,
+        })
+    }
+
+    /// The current instruction has an address mode that it can not use
+    fn invalid_address_mode(&self) -> Fault {
+        Fault::Cpu(CpuError::InvalidAddressMode {
+            pc: self.op_pc,
+            opcode: self.opcode.unwrap_or_default(),
+        })
+    }
+
+    /// Read a word from address and address + 1, wrapping around at the end of memory
+    fn read_word(&mut self, addr: impl Into<Word>) -> Result<Word, Fault> {
         let a1 = addr.into();
-        let a2 = a1 + 1;
+        let a2 = a1.wrapping_add(1);
         let addr = self.read(a1)?;
         let page = self.read(a2)?;
         Ok(Address::Full(addr, page).into())
     }
 
+    /// Read a word from the zero page, where the high byte wraps around to the start of the page
+    fn read_word_zero_page(&mut self, zero_page_addr: Byte) -> Result<Word, Fault> {
+        let addr = self.read(Address::Zero(zero_page_addr))?;
+        let page = self.read(Address::Zero(zero_page_addr.wrapping_add(1)))?;
+        Ok(Address::Full(addr, page).into())
+    }
+
     /// Fetch byte from memory that the PC points to and increment the PC
-    fn fetch(&mut self) -> Result<Byte, CpuError> {
+    fn fetch(&mut self) -> Result<Byte, Fault> {
         let data = self.read(self.PC)?;
-        self.PC += 1;
+        self.PC = self.PC.wrapping_add(1);
         Ok(data)
     }
 
     /// Fetch word from memory that the PC points to and increment the PC twice
-    fn fetch_word(&mut self) -> Result<Word, CpuError> {
+    fn fetch_word(&mut self) -> Result<Word, Fault> {
         let data = self.read_word(self.PC)?;
-        self.PC += 2;
+        self.PC = self.PC.wrapping_add(2);
         Ok(data)
     }
 
-    /// Read next op from memory without changing the PC
-    fn read_op(&self) -> Result<Op, CpuError> {
-        self.read(self.PC)?.try_into()
+    /// Read next op code from memory and decode it, without changing the PC
+    fn read_op(&mut self) -> Result<(Byte, Op, Handler<T>), Fault> {
+        let value = self.read(self.PC)?;
+        match self.decoder.get(value) {
+            Some((op, handler)) => Ok((value, op, handler)),
+            None => Err(CpuError::BadOpCode {
+                pc: self.PC,
+                opcode: value,
+            }
+            .into()),
+        }
     }
 
     /// Fetch op from memory that the PC points to and increment the PC
-    fn fetch_op(&mut self) -> Result<Op, CpuError> {
-        let op = self.read_op()?;
-        self.PC += 1;
-        Ok(op)
+    fn fetch_op(&mut self) -> Result<(Op, Handler<T>), Fault> {
+        let pc = self.PC;
+        let (value, op, handler) = self.read_op()?;
+        self.opcode = Some(value);
+        if let Some(trace) = &mut self.trace {
+            trace.pc = pc;
+            trace.op = Some((value, op.0, op.1));
+        }
+        self.PC = self.PC.wrapping_add(1);
+        Ok((op, handler))
+    }
+
+    /// Note the address the current instruction operates on for [`Cpu::step`]
+    fn target(&mut self, addr: Word) {
+        if let Some(trace) = &mut self.trace {
+            trace.addr = Some(addr);
+        }
     }
 
     /// Fetch memory address that is referenced by some address mode
-    fn fetch_addr(&mut self, mode: &AddressMode) -> Result<Word, CpuError> {
-        Ok(match mode {
-            AddressMode::Accumulator | AddressMode::Relative | AddressMode::Implicit => {
-                return Err(CpuError::InvalidAddressMode);
+    fn fetch_addr(&mut self, mode: AddressMode) -> Result<Word, Fault> {
+        let addr = match mode {
+            AddressMode::Accumulator
+            | AddressMode::Relative
+            | AddressMode::Implicit
+            | AddressMode::ZeroPageRelative => {
+                return Err(self.invalid_address_mode());
             }
             AddressMode::Immediate => {
-                self.PC += 1;
-                self.PC - 1
+                let addr = self.PC;
+                self.PC = self.PC.wrapping_add(1);
+                addr
             }
             AddressMode::ZeroPage => Address::Zero(self.fetch()?).into(),
-            AddressMode::ZeroPageX => Address::Zero(self.fetch()?.wrapping_add(self.X)).into(),
-            AddressMode::ZeroPageY => Address::Zero(self.fetch()?.wrapping_add(self.Y)).into(),
+            AddressMode::ZeroPageX => {
+                // The unindexed address is read while the index is added
+                let zero_page_addr = self.fetch()?;
+                self.read(Address::Zero(zero_page_addr))?;
+                Address::Zero(zero_page_addr.wrapping_add(self.X)).into()
+            }
+            AddressMode::ZeroPageY => {
+                let zero_page_addr = self.fetch()?;
+                self.read(Address::Zero(zero_page_addr))?;
+                Address::Zero(zero_page_addr.wrapping_add(self.Y)).into()
+            }
             AddressMode::Absolute => self.fetch_word()?,
-            AddressMode::AbsoluteX => self.fetch_word()? + u16::from(self.X),
-            AddressMode::AbsoluteY => self.fetch_word()? + u16::from(self.Y),
-            AddressMode::Indirect => todo!(),
+            AddressMode::AbsoluteX => {
+                let base = self.fetch_word()?;
+                self.index(base, self.X)?
+            }
+            AddressMode::AbsoluteY => {
+                let base = self.fetch_word()?;
+                self.index(base, self.Y)?
+            }
+            AddressMode::Indirect => {
+                let addr = self.fetch_word()?;
+                match (self.variant, Address::from(addr)) {
+                    // The NMOS 6502 does not carry into the page when fetching the high byte
+                    (Variant::Nmos6502, Address::Full(0xFF, page)) => {
+                        Address::Full(self.read(addr)?, self.read(Address::Full(0x00, page))?)
+                            .into()
+                    }
+                    (Variant::Nmos6502, _) => self.read_word(addr)?,
+                    // The 65C02 spends a cycle fixing that
+                    (Variant::Wdc65C02, _) => {
+                        self.read(self.PC.wrapping_sub(1))?;
+                        self.read_word(addr)?
+                    }
+                }
+            }
             AddressMode::IndexedIndirect => {
                 let zero_page_addr = self.fetch()?;
-                let addr_with_offset = zero_page_addr.wrapping_add(self.X);
-                self.read_word(addr_with_offset)?
+                self.read(Address::Zero(zero_page_addr))?;
+                self.read_word_zero_page(zero_page_addr.wrapping_add(self.X))?
             }
             AddressMode::IndirectIndexed => {
                 let zero_page_addr = self.fetch()?;
-                let target_addr = self.read_word(Address::Zero(zero_page_addr))?;
-                target_addr + u16::from(self.Y)
+                let target_addr = self.read_word_zero_page(zero_page_addr)?;
+                self.index(target_addr, self.Y)?
             }
-        })
+            AddressMode::ZeroPageIndirect => {
+                let zero_page_addr = self.fetch()?;
+                self.read_word_zero_page(zero_page_addr)?
+            }
+            AddressMode::AbsoluteIndexedIndirect => {
+                let base = self.fetch_word()?;
+                self.read(self.PC.wrapping_sub(1))?;
+                self.read_word(base.wrapping_add(Word::from(self.X)))?
+            }
+        };
+        self.target(addr);
+        Ok(addr)
+    }
+
+    /// Add an index register to a base address, noting if that crosses a page. The 6502 spends a
+    /// cycle fixing the high byte of the address, which reads from the address without the carry
+    /// on the NMOS 6502 and the last operand byte on the 65C02. Reads skip that cycle if indexing
+    /// stays on the page.
+    fn index(&mut self, base: Word, index: Byte) -> Result<Word, Fault> {
+        let addr = base.wrapping_add(Word::from(index));
+        self.page_crossed = base.to_be_bytes()[0] != addr.to_be_bytes()[0];
+        if self.page_crossed || !self.page_penalty {
+            match self.variant {
+                Variant::Nmos6502 => {
+                    let unfixed = Address::Full(addr.to_le_bytes()[0], base.to_le_bytes()[1]);
+                    self.read(unfixed)?
+                }
+                Variant::Wdc65C02 => self.read(self.PC.wrapping_sub(1))?,
+            };
+        }
+        Ok(addr)
     }
 
     /// Write byte to address
-    fn write(&mut self, addr: impl Into<Word>, value: impl Into<Byte>) -> Result<(), CpuError> {
-        let addr = addr.into();
-        if self.addr.inside_bounds(addr) {
-            self.addr[addr] = value.into();
-            Ok(())
-        } else {
-            Err(CpuError::OutOfBounds(addr))
+    fn write(&mut self, addr: impl Into<Word>, value: impl Into<Byte>) -> Result<(), Fault> {
+        if self.steps.replay()?.is_some() {
+            return Ok(());
+        }
+        // The NMOS 6502 ignores RDY on writes
+        if self.steps.active && self.pins.rdy.asserted() && self.variant == Variant::Wdc65C02 {
+            return Err(Fault::Suspended);
+        }
+        let (addr, data) = (addr.into(), value.into());
+        if !self.addr.inside_bounds(addr) {
+            return Err(self.bus_fault(BusError::OutOfBounds(addr)));
+        }
+        if !self.addr.writable(addr) {
+            return Err(self.bus_fault(BusError::ReadOnly(addr)));
         }
+        self.addr.write(addr, data);
+        self.note(Access {
+            addr,
+            data,
+            write: true,
+        });
+        Ok(())
     }
 
     /// Push byte onto the stack
-    fn stack_push(&mut self, byte: impl Into<Byte>) -> Result<(), CpuError> {
+    fn stack_push(&mut self, byte: impl Into<Byte>) -> Result<(), Fault> {
         let addr = Address::from((self.SP, SP_PAGE));
         self.write(addr, byte.into())?;
         self.SP = self.SP.wrapping_sub(1);
         Ok(())
     }
 
+    /// Read the byte on top of the stack without popping it, which the 6502 does while it
+    /// increments the stack pointer
+    fn stack_read(&mut self) -> Result<Byte, Fault> {
+        self.read(Address::from((self.SP, SP_PAGE)))
+    }
+
     /// Pop byte from the stack
-    fn stack_pop(&mut self) -> Result<Byte, CpuError> {
+    fn stack_pop(&mut self) -> Result<Byte, Fault> {
         self.SP = self.SP.wrapping_add(1);
         let addr = Address::from((self.SP, SP_PAGE));
         let data = self.read(addr)?;
         Ok(data)
     }
 
-    /// Get status register
-    fn status_get(&self) -> Byte {
-        let mut res = 0;
-        if self.C {
-            res |= C_FLAG;
+    /// Apply the flags produced by the ALU and return its result
+    fn alu(&mut self, out: alu::Output) -> Byte {
+        if let Some(c) = out.c {
+            self.C = c;
         }
-        if self.Z {
-            res |= Z_FLAG;
+        if let Some(z) = out.z {
+            self.Z = z;
         }
-        if self.I {
-            res |= I_FLAG;
+        if let Some(v) = out.v {
+            self.V = v;
         }
-        if self.D {
-            res |= D_FLAG;
+        if let Some(n) = out.n {
+            self.N = n;
         }
-        if self.B {
-            res |= B_FLAG;
+        out.result
+    }
+
+    /// Add memory to the accumulator, with the decimal mode flags of the variant
+    fn add(&mut self, data: Byte) {
+        let out = match self.variant {
+            Variant::Nmos6502 => alu::adc(self.A, data, self.C, self.D),
+            Variant::Wdc65C02 => alu::adc_cmos(self.A, data, self.C, self.D),
+        };
+        self.A = self.alu(out);
+    }
+
+    /// Subtract memory from the accumulator, with the decimal mode flags of the variant
+    fn sub(&mut self, data: Byte) {
+        let out = match self.variant {
+            Variant::Nmos6502 => alu::sbc(self.A, data, self.C, self.D),
+            Variant::Wdc65C02 => alu::sbc_cmos(self.A, data, self.C, self.D),
+        };
+        self.A = self.alu(out);
+    }
+
+    /// Push the return address and status and jump through an interrupt vector. A BRK or IRQ
+    /// goes to the NMI vector instead if an NMI comes in before the vector is read.
+    fn interrupt(&mut self, vector: Word, brk: bool) -> Result<(), Fault> {
+        let [addr, page] = self.PC.to_le_bytes();
+        self.stack_push(page)?;
+        self.stack_push(addr)?;
+        let vector = if vector == IRQB && self.interrupts.hijacked() {
+            NMIB
+        } else {
+            vector
+        };
+        self.stack_push(self.status_get().pushed(brk))?;
+
+        self.PC = self.read_word(vector)?;
+
+        self.I = true;
+
+        // The 65C02 leaves the interrupt handler in binary mode
+        if self.variant == Variant::Wdc65C02 {
+            self.D = false;
         }
-        if self.V {
-            res |= V_FLAG;
+
+        Ok(())
+    }
+
+    /// Go through the motions of an interrupt with the stack writes turned into reads, then jump
+    /// through the reset vector
+    fn reset_sequence(&mut self) -> Result<(), Fault> {
+        for _ in 0..3 {
+            self.stack_read()?;
+            self.SP = self.SP.wrapping_sub(1);
         }
-        if self.N {
-            res |= N_FLAG;
+
+        self.PC = self.read_word(RESB)?;
+
+        self.I = true;
+
+        // The 65C02 starts in binary mode, the NMOS 6502 leaves the decimal flag undefined
+        if self.variant == Variant::Wdc65C02 {
+            self.D = false;
         }
-        res
+
+        Ok(())
     }
 
-    /// Set status register
-    fn status_set(&mut self, status: Byte) {
-        self.C = status & C_FLAG > 0;
-        self.Z = status & Z_FLAG > 0;
-        self.I = status & I_FLAG > 0;
-        self.D = status & D_FLAG > 0;
-        self.B = status & B_FLAG > 0;
-        self.V = status & V_FLAG > 0;
-        self.N = status & N_FLAG > 0;
+    /// Run a read-modify-write operation on either the accumulator or memory, writing the result
+    /// back. While modifying, the NMOS 6502 writes the unmodified value back and the 65C02 reads
+    /// it again.
+    fn modify(
+        &mut self,
+        mode: AddressMode,
+        op: impl FnOnce(Byte) -> alu::Output,
+    ) -> Result<Byte, Fault> {
+        if matches!(mode, AddressMode::Accumulator) {
+            self.A = self.alu(op(self.A));
+            Ok(self.A)
+        } else {
+            let addr = self.fetch_addr(mode)?;
+            let data = self.read(addr)?;
+            match self.variant {
+                Variant::Nmos6502 => self.write(addr, data)?,
+                Variant::Wdc65C02 => {
+                    self.read(addr)?;
+                }
+            }
+            let data = self.alu(op(data));
+            self.write(addr, data)?;
+            Ok(data)
+        }
+    }
+
+    /// Store a register AND the high byte of the base address plus one, like the unstable
+    /// SHA, SHX, SHY and TAS do. If indexing crosses a page the value replaces the high byte of
+    /// the target address.
+    fn store_high(&mut self, mode: AddressMode, value: Byte) -> Result<(), Fault> {
+        let index = match mode {
+            AddressMode::AbsoluteX => self.X,
+            _ => self.Y,
+        };
+        let addr = self.fetch_addr(mode)?;
+        let [lo, hi] = addr.wrapping_sub(Word::from(index)).to_le_bytes();
+        let value = value & hi.wrapping_add(1);
+        if lo.checked_add(index).is_none() {
+            self.write(Address::Full(lo.wrapping_add(index), value), value)
+        } else {
+            self.write(addr, value)
+        }
+    }
+
+    /// Set how undocumented opcodes are handled
+    pub fn illegal_opcodes_set(&mut self, illegal: IllegalOpCodes) {
+        self.decoder = Decoder::new(self.variant, illegal);
+    }
+
+    /// Fill the registers and flags like the power just came on, they are zero by default.
+    /// [`Resettable::reset`] then starts the processor.
+    pub fn power_on(&mut self, power_on: PowerOn) {
+        let mut bytes = power_on.bytes();
+        let mut byte = || bytes.next().unwrap_or_default();
+        self.PC = Word::from_le_bytes([byte(), byte()]);
+        self.SP = byte();
+        self.A = byte();
+        self.X = byte();
+        self.Y = byte();
+        self.status_set(Status::from(byte()));
+    }
+
+    /// Program Counter
+    pub fn pc_get(&self) -> Word {
+        self.PC
+    }
+
+    /// Set the program counter, the next instruction is fetched from there. Like the other
+    /// register setters it is meant to be used between instructions.
+    pub fn pc_set(&mut self, pc: Word) {
+        self.PC = pc;
+    }
+
+    /// Stack Pointer
+    pub fn sp_get(&self) -> Byte {
+        self.SP
+    }
+
+    /// Set the stack pointer
+    pub fn sp_set(&mut self, sp: Byte) {
+        self.SP = sp;
+    }
+
+    /// Accumulator
+    pub fn a_get(&self) -> Byte {
+        self.A
+    }
 
-        assert!(!self.D, "Decimal Mode not supported");
+    /// Set the accumulator
+    pub fn a_set(&mut self, a: Byte) {
+        self.A = a;
     }
 
-    /// Set mode
-    #[allow(dead_code)]
-    fn mode_set(&mut self, mode: Mode) {
+    /// Index Register X
+    pub fn x_get(&self) -> Byte {
+        self.X
+    }
+
+    /// Set index register X
+    pub fn x_set(&mut self, x: Byte) {
+        self.X = x;
+    }
+
+    /// Index Register Y
+    pub fn y_get(&self) -> Byte {
+        self.Y
+    }
+
+    /// Set index register Y
+    pub fn y_set(&mut self, y: Byte) {
+        self.Y = y;
+    }
+
+    /// Status register
+    pub fn status_get(&self) -> Status {
+        let mut status = Status::default();
+        status.carry_set(self.C);
+        status.zero_set(self.Z);
+        status.interrupt_disable_set(self.I);
+        status.decimal_set(self.D);
+        status.overflow_set(self.V);
+        status.negative_set(self.N);
+        status
+    }
+
+    /// Set the status register
+    pub fn status_set(&mut self, status: Status) {
+        self.C = status.carry_get();
+        self.Z = status.zero_get();
+        self.I = status.interrupt_disable_get();
+        self.D = status.decimal_get();
+        self.V = status.overflow_get();
+        self.N = status.negative_get();
+    }
+
+    /// Clock cycles used since reset
+    pub fn cycles_get(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Instructions retired since reset, not counting interrupt and reset sequences
+    pub fn instructions_get(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Handle to the RDY pin, asserting it pulls RDY low. That stalls the processor on its next
+    /// read, or any access on the 65C02, until it is released. Only stepped mode stalls in the
+    /// middle of an instruction, the other modes stall before fetching the next op code.
+    pub fn rdy(&self) -> Line {
+        self.pins.rdy.clone()
+    }
+
+    /// Handle to the SO pin, asserting it pulls SO low. The falling edge sets the overflow flag.
+    pub fn so(&self) -> Line {
+        self.pins.so.clone()
+    }
+
+    /// Whether SYNC was high on the last cycle, which it is while an op code is being fetched
+    pub fn sync_get(&self) -> bool {
+        self.pins.sync
+    }
+
+    /// The mode the Cpu runs in, which decides how many clock cycles a tick stands for
+    pub fn mode_get(&self) -> Mode {
+        self.mode
+    }
+
+    /// Set mode, an instruction that is being stepped through starts over
+    pub fn mode_set(&mut self, mode: Mode) {
+        if !self.steps.done.is_empty() {
+            self.steps = Steps::default();
+        }
         self.mode = mode;
     }
 
-    pub fn tick_until_nop(&mut self) -> Result<(), Box<dyn Error>> {
-        let mut count = 0;
-        loop {
-            if matches!(self.read_op()?, Op(OpCode::Nop, _, _)) {
-                return Ok(());
-            }
+    /// Get the registers and flags an instruction can change
+    fn state_get(&self) -> State {
+        State {
+            PC: self.PC,
+            SP: self.SP,
+            A: self.A,
+            X: self.X,
+            Y: self.Y,
+            C: self.C,
+            Z: self.Z,
+            I: self.I,
+            D: self.D,
+            V: self.V,
+            N: self.N,
+            run: self.run,
+        }
+    }
 
-            count += 1;
-            if count > 2000 {
-                return Err("endless Loop".into());
-            }
+    /// Restore the registers and flags an instruction can change
+    fn state_set(&mut self, state: State) {
+        self.PC = state.PC;
+        self.SP = state.SP;
+        self.A = state.A;
+        self.X = state.X;
+        self.Y = state.Y;
+        self.C = state.C;
+        self.Z = state.Z;
+        self.I = state.I;
+        self.D = state.D;
+        self.V = state.V;
+        self.N = state.N;
+        self.run = state.run;
+    }
 
-            self.tick()?;
+    /// Finish the instruction that runs, polling for interrupts
+    fn retire(&mut self) {
+        if self.retiring {
+            self.instructions += 1;
         }
+        self.interrupts.poll();
     }
 
-    pub fn tick_for(&mut self, count: usize) -> Result<(), Box<dyn Error>> {
-        for _ in 0..count {
-            self.tick()?;
+    /// Run the current instruction up to and including its next bus access, returning true once
+    /// the instruction is done
+    fn step_cycle(&mut self) -> Result<bool, CpuError> {
+        let state = self.state_get();
+        self.steps.active = true;
+        self.steps.replayed = 0;
+        self.steps.before = self.steps.done.len();
+        let res = self.run();
+        self.steps.active = false;
+
+        if res == Ok(None) {
+            // Registers only change once the instruction is done
+            self.state_set(state);
+            return Ok(false);
         }
-        Ok(())
+        self.steps.done.clear();
+        res.map(|_| true)
     }
-}
 
-macro_rules! flags_zn {
-    ($self:ident, $r:expr) => {
-        $self.Z = $r == 0;
-        $self.N = ($r & 0b1000_0000) > 0;
-    };
-}
+    /// Execute the next instruction, returning how many cycles it used after the first one or
+    /// `None` if stepped mode suspended it
+    fn run(&mut self) -> Result<Option<u8>, CpuError> {
+        match self.execute() {
+            Ok(noop) => Ok(Some(noop)),
+            Err(Fault::Suspended) => Ok(None),
+            Err(Fault::Cpu(e)) => {
+                #[cfg(feature = "tracing")]
+                self.log_error(&e);
+                Err(e)
+            }
+        }
+    }
 
-macro_rules! compare {
-    ($self:ident, $r:ident, $mode:ident) => {
-        let addr = $self.fetch_addr(&$mode)?;
-        let data = $self.read(addr)?;
-        let res = $self.$r.wrapping_sub(data);
-        $self.C = $self.$r >= data;
-        $self.Z = $self.$r == data;
-        $self.N = (res & 0b1000_0000) > 0;
-    };
-}
+    /// Whether the Cpu is between instructions
+    fn boundary(&self) -> bool {
+        self.burn == 0 && self.steps.done.is_empty()
+    }
 
-macro_rules! branch {
-    ($self:ident, $cond:expr) => {{
-        let offset = $self.fetch()?;
-        if $cond {
-            $self.PC = u16::try_from(i32::from($self.PC) + i32::from(offset))
-                .map_err(|e| format!("failed to calculate offset: {e}"))?;
+    /// Run the next instruction to the end and report what it did. An instruction that is
+    /// already under way in original or stepped mode is finished first, without a report. Returns
+    /// `None` if no instruction ran, as the processor waits, is halted or is held by RDY.
+    pub fn step(&mut self) -> Result<Option<Record>, CpuError> {
+        while !self.boundary() {
+            if self.mode == Mode::Stepped && self.pins.rdy.asserted() {
+                return Ok(None);
+            }
+            self.clock()?;
+        }
+
+        let cycles = self.cycles;
+        self.trace = Some(Trace::default());
+        let res = self.clock().and_then(|()| {
+            while !self.boundary() {
+                if self.mode == Mode::Stepped && self.pins.rdy.asserted() {
+                    return Ok(false);
+                }
+                self.clock()?;
+            }
+            Ok(true)
+        });
+        let trace = self.trace.take();
 
-            true
+        if res? {
+            Ok(trace.and_then(|trace| trace.finish(self.cycles - cycles)))
         } else {
-            false
+            Ok(None)
         }
-    }};
+    }
+
+    /// Tick until the next instruction is the documented NOP ($EA) or the processor halts, giving
+    /// up after 2000 ticks
+    pub fn tick_until_nop(&mut self) -> Result<(), CpuError> {
+        const LIMIT: usize = 2000;
+        for _ in 0..=LIMIT {
+            if matches!(self.run, RunState::Stopped | RunState::Jammed(_)) {
+                return Ok(());
+            }
+            let next = self.peek(self.PC).map_err(|source| CpuError::Bus {
+                pc: self.PC,
+                opcode: None,
+                source,
+            })?;
+            // Undocumented op codes can decode to NOP too, and programs run those on the way
+            if self.interrupts.pending.is_none()
+                && next == 0xEA
+                && matches!(self.decoder.get(next), Some((Op(OpCode::Nop, _, _), _)))
+            {
+                return Ok(());
+            }
+
+            self.clock()?;
+        }
+        Err(CpuError::EndlessLoop(LIMIT))
+    }
+
+    pub fn tick_for(&mut self, count: usize) -> Result<(), CpuError> {
+        for _ in 0..count {
+            self.clock()?;
+        }
+        Ok(())
+    }
 }
 
-impl<T> Tickable for Cpu<T>
+impl<T> Cpu<T>
 where
-    T: Addressable,
+    T: Device,
 {
+    /// Execute the next instruction, returning how many cycles it used after the first one
     #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
-    fn tick(&mut self) -> Result<(), Box<dyn Error>> {
-        // Burn cycles if we need to
-        if let Mode::Original(noop) = self.mode
-            && noop > 0
-        {
-            self.mode = Mode::Original(noop - 1);
-            return Ok(());
+    fn execute(&mut self) -> Result<u8, Fault> {
+        self.page_crossed = false;
+        self.op_pc = self.PC;
+        self.opcode = None;
+
+        if let Some(vector) = self.interrupts.pending {
+            if let Some(trace) = &mut self.trace {
+                trace.pc = self.PC;
+                trace.op = Some((0x00, OpCode::Brk, AddressMode::Implicit));
+                trace.interrupt = Some(vector);
+            }
+
+            // The op code fetch is thrown away without moving the PC, then it runs like BRK
+            self.read(self.PC)?;
+            self.read(self.PC)?;
+            if vector == RESB {
+                self.reset_sequence()?;
+            } else {
+                self.interrupt(vector, false)?;
+            }
+            self.interrupts.pending = None;
+            self.interrupts.done();
+            self.interrupts.poll_i = self.I;
+            self.interrupts.poll_second = false;
+            self.retiring = false;
+            #[cfg(feature = "tracing")]
+            self.log_interrupt(vector);
+            return Ok(6);
         }
 
-        let Op(op, mode, cycles) = dbg!(self.fetch_op()?);
+        let i = self.I;
+        let (Op(op, mode, cycles), handler) = self.fetch_op()?;
+        let delayed = matches!(op, OpCode::Cli | OpCode::Sei | OpCode::Plp);
+        self.page_penalty = matches!(cycles, Cycles::Page(_));
         let mut noop = match cycles {
             Cycles::Constant(c) | Cycles::Page(c) | Cycles::Branch(c) => c,
         };
@@ -339,410 +1052,256 @@ where
         // We used 1 cycle to fetch the op code, so no need to burn that
         noop -= 1;
 
+        // Instructions without an operand still read the byte after the op code
+        if matches!(mode, AddressMode::Implicit | AddressMode::Accumulator) && noop > 0 {
+            self.read(self.PC)?;
+        }
+        let base = noop;
+
         // Execute op code
-        match (op, mode) {
-            (OpCode::Lda, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.A = self.read(addr)?;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Ldx, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.X = self.read(addr)?;
-                flags_zn!(self, self.X);
-            }
-            (OpCode::Ldy, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.Y = self.read(addr)?;
-                flags_zn!(self, self.Y);
-            }
-            (OpCode::Sta, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.write(addr, self.A)?;
-            }
-            (OpCode::Stx, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.write(addr, self.X)?;
-            }
-            (OpCode::Sty, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.write(addr, self.Y)?;
-            }
-            (OpCode::Tax, _) => {
-                self.X = self.A;
-                flags_zn!(self, self.X);
-            }
-            (OpCode::Tay, _) => {
-                self.Y = self.A;
-                flags_zn!(self, self.Y);
-            }
-            (OpCode::Txa, _) => {
-                self.A = self.X;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Tya, _) => {
-                self.A = self.Y;
-                flags_zn!(self, self.Y);
-            }
-            (OpCode::Tsx, _) => {
-                self.X = self.SP;
-                flags_zn!(self, self.X);
-            }
-            (OpCode::Txs, _) => {
-                self.SP = self.X;
-            }
-            (OpCode::Pha, _) => {
-                self.stack_push(self.A)?;
-            }
-            (OpCode::Php, _) => {
-                self.stack_push(self.status_get())?;
-            }
-            (OpCode::Pla, _) => {
-                self.A = self.stack_pop()?;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Plp, _) => {
-                let status = self.stack_pop()?;
-                self.status_set(status);
-            }
-            (OpCode::And, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.A &= self.read(addr)?;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Eor, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.A ^= self.read(addr)?;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Ora, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                self.A |= self.read(addr)?;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Bit, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                let data = self.read(addr)?;
-                self.Z = (data & self.A) == 0;
-                self.N = (data & 0b1000_0000) > 0;
-                self.V = (data & 0b0100_0000) > 0;
-            }
-            (OpCode::Adc, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                let data = self.read(addr)?;
-                let (result1, carry1) = self.A.overflowing_add(data);
-                let (result2, carry2) = result1.overflowing_add(u8::from(self.C));
-                self.A = result2;
-                self.C = carry1 || carry2;
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Sbc, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                let data = self.read(addr)?;
-                let (result1, carry1) = self.A.overflowing_sub(data);
-                let (result2, carry2) = result1.overflowing_sub(u8::from(!self.C));
-                self.A = result2;
-                self.C = !(carry1 || carry2);
-                flags_zn!(self, self.A);
-            }
-            (OpCode::Cmp, mode) => {
-                compare!(self, A, mode);
-            }
-            (OpCode::Cpx, mode) => {
-                compare!(self, X, mode);
-            }
-            (OpCode::Cpy, mode) => {
-                compare!(self, Y, mode);
-            }
-            (OpCode::Inc, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                let mut data = self.read(addr)?;
-                data = data.wrapping_add(1);
-                self.write(addr, data)?;
-                flags_zn!(self, data);
-            }
-            (OpCode::Inx, _) => {
-                self.X = self.X.wrapping_add(1);
-                flags_zn!(self, self.X);
-            }
-            (OpCode::Iny, _) => {
-                self.Y = self.Y.wrapping_add(1);
-                flags_zn!(self, self.Y);
-            }
-            (OpCode::Dec, mode) => {
-                let addr = self.fetch_addr(&mode)?;
-                let mut data = self.read(addr)?;
-                data = data.wrapping_sub(1);
-                self.write(addr, data)?;
-                flags_zn!(self, data);
-            }
-            (OpCode::Dex, _) => {
-                self.X = self.X.wrapping_sub(1);
-                flags_zn!(self, self.X);
-            }
-            (OpCode::Dey, _) => {
-                self.Y = self.Y.wrapping_sub(1);
-                flags_zn!(self, self.Y);
-            }
-            (OpCode::Asl, mode) => {
-                let (addr, mut data) = if matches!(mode, AddressMode::Accumulator) {
-                    (None, self.A)
-                } else {
-                    let addr = self.fetch_addr(&mode)?;
-                    let data = self.read(addr)?;
-                    (Some(addr), data)
-                };
-
-                self.C = data & 0b1000_0000 > 0;
-                data <<= 1;
-
-                if let Some(addr) = addr {
-                    self.write(addr, data)?;
-                } else {
-                    self.A = data;
-                }
+        noop += handler(self, Op(op, mode, cycles))?;
 
-                self.Z = data == 0;
-                self.N = data & 0b1000_0000 > 0;
-            }
-            (OpCode::Lsr, mode) => {
-                let (addr, mut data) = if matches!(mode, AddressMode::Accumulator) {
-                    (None, self.A)
-                } else {
-                    let addr = self.fetch_addr(&mode)?;
-                    let data = self.read(addr)?;
-                    (Some(addr), data)
-                };
-
-                self.C = data & 0b0000_0001 > 0;
-                data >>= 1;
-
-                if let Some(addr) = addr {
-                    self.write(addr, data)?;
-                } else {
-                    self.A = data;
-                }
+        if matches!(cycles, Cycles::Page(_)) && self.page_crossed {
+            noop += 1;
+        }
 
-                // flags_zn!(self, data); < Docs said this, and I dont believe it!
-                self.Z = data == 0;
-                self.N = data & 0b1000_0000 > 0;
-            }
-            (OpCode::Rol, mode) => {
-                let (addr, mut data) = if matches!(mode, AddressMode::Accumulator) {
-                    (None, self.A)
-                } else {
-                    let addr = self.fetch_addr(&mode)?;
-                    let data = self.read(addr)?;
-                    (Some(addr), data)
-                };
-
-                let old_c = self.C;
-                self.C = data & 0b1000_0000 > 0;
-                data <<= 1;
-                data += u8::from(old_c);
-
-                if let Some(addr) = addr {
-                    self.write(addr, data)?;
-                } else {
-                    self.A = data;
-                }
+        // Flag changes by CLI, SEI and PLP only affect polling after the next instruction, and
+        // taken branches that stay on the page poll on their second cycle
+        self.interrupts.poll_i = if delayed { i } else { self.I };
+        self.interrupts.poll_second = matches!(cycles, Cycles::Branch(_)) && noop == base + 1;
+        self.interrupts.done();
+        self.retiring = true;
+        #[cfg(feature = "tracing")]
+        self.log_instruction(op, mode);
 
-                self.Z = data == 0;
-                self.N = data & 0b1000_0000 > 0;
-            }
-            (OpCode::Ror, mode) => {
-                let (addr, mut data) = if matches!(mode, AddressMode::Accumulator) {
-                    (None, self.A)
-                } else {
-                    let addr = self.fetch_addr(&mode)?;
-                    let data = self.read(addr)?;
-                    (Some(addr), data)
-                };
-
-                let old_c = self.C;
-                self.C = data & 0b0000_0001 > 0;
-                data >>= 1;
-                if old_c {
-                    data |= 0b1000_0000;
-                }
+        Ok(noop)
+    }
+}
 
-                if let Some(addr) = addr {
-                    self.write(addr, data)?;
-                } else {
-                    self.A = data;
-                }
+impl<T> Cpu<T>
+where
+    T: Device,
+{
+    /// Run a single clock cycle, or a whole instruction in fast mode
+    fn clock(&mut self) -> Result<(), CpuError> {
+        // Interrupts are polled at the start of the last cycle of an instruction
+        let first = match self.mode {
+            Mode::Fast | Mode::Original => self.burn == 0,
+            Mode::Stepped => self.steps.done.is_empty(),
+        };
+        self.interrupts.sample(first);
 
-                self.Z = data == 0;
-                self.N = data & 0b1000_0000 > 0;
-            }
-            (OpCode::Jmp, AddressMode::Absolute) => {
-                self.PC = self.fetch_word()?;
-            }
-            (OpCode::Jmp, AddressMode::Indirect) => {
-                let addr = self.fetch_word()?;
-                self.PC = self.read_word(addr)?;
-            }
-            (OpCode::Jsr, _) => {
-                let new_pc = self.fetch_word()?;
-                let Address::Full(addr, page) = Address::from(self.PC - 1) else {
-                    return Err("could not construct address from PC".into());
-                };
-                self.stack_push(page)?;
-                self.stack_push(addr)?;
-                self.PC = new_pc;
-            }
-            (OpCode::Rts, _) => {
-                let addr = self.stack_pop()?;
-                let page = self.stack_pop()?;
-                self.PC = Address::Full(addr, page).into();
-                self.PC += 1;
-            }
-            (OpCode::Bcc, _) => {
-                if branch!(self, !self.C) {
-                    noop += 1;
-                }
-            }
-            (OpCode::Bcs, _) => {
-                if branch!(self, self.C) {
-                    noop += 1;
-                }
-            }
-            (OpCode::Beq, _) => {
-                if branch!(self, self.Z) {
-                    noop += 1;
-                }
+        if self.pins.set_overflow() {
+            self.V = true;
+        }
+
+        // Burn cycles if we need to
+        if self.burn > 0 {
+            self.pins.sync = false;
+            self.burn -= 1;
+            self.cycles += 1;
+            if self.burn == 0 {
+                self.retire();
             }
-            (OpCode::Bmi, _) => {
-                if branch!(self, self.N) {
-                    noop += 1;
-                }
+            return Ok(());
+        }
+
+        match self.run {
+            RunState::Running => {}
+            // WAI holds the processor until an interrupt arrives
+            RunState::Waiting if self.interrupts.wake(self.I) => {
+                self.run = RunState::Running;
             }
-            (OpCode::Bne, _) => {
-                if branch!(self, !self.Z) {
-                    noop += 1;
-                }
+            // Only a reset gets a stopped or jammed processor going again
+            RunState::Waiting | RunState::Stopped | RunState::Jammed(_) => {
+                self.pins.sync = false;
+                self.cycles += 1;
+                return Ok(());
             }
-            (OpCode::Bpl, _) => {
-                if branch!(self, !self.N) {
-                    noop += 1;
-                }
+        }
+
+        // RDY low holds the op code fetch, stepped mode stalls on the access itself
+        self.pins.sync = first;
+        if self.pins.rdy.asserted() && !matches!(self.mode, Mode::Stepped) {
+            self.cycles += 1;
+            return Ok(());
+        }
+
+        match self.mode {
+            // Only stepped mode suspends instructions, the others always run them to the end
+            Mode::Fast => {
+                let noop = self.run()?.unwrap_or_default();
+                self.cycles += u64::from(noop) + 1;
+                self.retire();
             }
-            (OpCode::Bvc, _) => {
-                if branch!(self, !self.V) {
-                    noop += 1;
+            Mode::Original => {
+                self.burn = self.run()?.unwrap_or_default();
+                self.cycles += 1;
+                if self.burn == 0 {
+                    self.retire();
                 }
             }
-            (OpCode::Bvs, _) => {
-                if branch!(self, self.V) {
-                    noop += 1;
+            Mode::Stepped => {
+                self.cycles += 1;
+                if self.step_cycle()? {
+                    self.retire();
                 }
             }
-            (OpCode::Clc, _) => {
-                self.C = false;
-            }
-            (OpCode::Cld, _) => {
-                self.D = false;
-            }
-            (OpCode::Cli, _) => {
-                self.I = false;
-            }
-            (OpCode::Clv, _) => {
-                self.V = false;
-            }
-            (OpCode::Sec, _) => {
-                self.C = true;
-            }
-            (OpCode::Sed, _) => {
-                self.D = true;
-                return Err("decimal Mode not supported".into());
-            }
-            (OpCode::Sei, _) => {
-                self.I = true;
-            }
-            (OpCode::Brk, _) => {
-                self.interrupt(0)?;
-            }
-            (OpCode::Rti, _) => {
-                self.I = false;
-
-                let status = self.stack_pop()?;
-                self.status_set(status);
-
-                let addr = self.stack_pop()?;
-                let page = self.stack_pop()?;
-                self.PC = Address::Full(addr, page).into();
-                self.PC += 1;
-            }
-            (OpCode::Nop, _) => {}
-            (op, mode) => todo!("{:?}({:?})", op, mode),
-        }
-
-        if let Mode::Original(_) = self.mode {
-            self.mode = Mode::Original(noop);
         }
 
         Ok(())
     }
 }
 
-impl<T> Interruptible for Cpu<T>
+impl<T> Tickable for Cpu<T>
 where
-    T: Addressable,
+    T: Device,
 {
-    fn interrupt(
-        &mut self,
-        tp: impl Into<crate::Interrupt>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let int_addr = match tp.into() {
-            0 => IRQB,
-            _ => NMIB,
-        };
-
-        if self.I && int_addr == IRQB {
-            return Ok(());
-        }
-
-        let Address::Full(addr, page) = Address::from(self.PC - 1) else {
-            return Err("could not construct address from PC".into());
-        };
-        self.stack_push(page)?;
-        self.stack_push(addr)?;
-        self.PC = self.read_word(int_addr)?;
+    fn tick(&mut self) -> Result<(), crate::Error> {
+        Ok(self.clock()?)
+    }
 
-        self.stack_push(self.status_get())?;
+    fn run_state_get(&self) -> RunState {
+        self.run
+    }
+}
 
-        self.I = true;
+impl<T> Interruptible for Cpu<T>
+where
+    T: Device,
+{
+    fn irq(&self) -> Line {
+        self.interrupts.irq.clone()
+    }
 
-        Ok(())
+    fn nmi(&self) -> Line {
+        self.interrupts.nmi.clone()
     }
 }
 
 impl<T> Resettable for Cpu<T>
 where
-    T: Addressable,
+    T: Device,
 {
-    /// Reset processor
-    fn reset(&mut self) -> Result<(), Box<dyn Error>> {
-        self.SP = SP_ADDR;
-        self.PC = self.read_word(RESB)?;
-
-        self.A = 0;
-        self.X = 0;
-        self.Y = 0;
+    /// Reset processor. The reset sequence takes the next 7 cycles, leaving SP 3 lower and PC at
+    /// the reset vector, while the other registers keep their values.
+    fn reset(&mut self) -> Result<(), crate::Error> {
+        self.run = RunState::Running;
+        self.burn = 0;
+        self.cycles = 0;
+        self.instructions = 0;
+        self.steps = Steps::default();
+        self.interrupts.reset();
+        self.interrupts.pending = Some(RESB);
 
-        // * => Set by software?
-        // self.C = false; // *
-        // self.Z = false; // *
-        self.I = false;
-        self.D = false;
-        self.B = false;
-        // self.V = false; // *
-        // self.N = false; // *
+        Ok(())
+    }
+}
 
-        if let Mode::Original(_) = self.mode {
-            self.mode = Mode::Original(0);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Memory;
+
+    #[test]
+    fn test_original_mode_cycles() {
+        // LDX #$01, LDA $10FF,X, NOP
+        let program = [0xA2, 0x01, 0xBD, 0xFF, 0x10, 0xEA];
+        let mut cpu = Cpu::new(Memory::from(&program[..]));
+        cpu.mode_set(Mode::Original);
+
+        // LDX takes 2 cycles and LDA 5, as indexing crosses a page
+        for (cycles, instructions) in [(1, 0), (2, 1), (3, 1), (4, 1), (5, 1), (6, 1), (7, 2)] {
+            cpu.tick().expect("Tick failed");
+            assert_eq!(cpu.cycles_get(), cycles);
+            assert_eq!(cpu.instructions_get(), instructions);
         }
+        assert_eq!(cpu.PC, 0x0005);
+        assert_eq!(cpu.burn, 0);
+    }
 
-        Ok(())
+    fn access(addr: Word, data: Byte, write: bool) -> Access {
+        Access { addr, data, write }
+    }
+
+    #[test]
+    fn test_stepped_mode_accesses() {
+        // LDX #$01, LDA $10FF,X, INC $1000, NOP
+        let program = [0xA2, 0x01, 0xBD, 0xFF, 0x10, 0xEE, 0x00, 0x10, 0xEA];
+        let mut memory = Memory::from(&program[..]);
+        memory[0x1000] = 0x41;
+        let mut cpu = Cpu::new(memory);
+        cpu.mode_set(Mode::Stepped);
+        cpu.tick_for(2).expect("Tick failed");
+        assert_eq!(cpu.X, 0x01);
+
+        // Indexing across a page reads the address without the carry first
+        cpu.tick_for(4).expect("Tick failed");
+        assert_eq!(
+            cpu.steps.done,
+            [
+                access(0x0002, 0xBD, false),
+                access(0x0003, 0xFF, false),
+                access(0x0004, 0x10, false),
+                access(0x1000, 0x41, false),
+            ]
+        );
+        cpu.tick().expect("Tick failed");
+        assert!(cpu.steps.done.is_empty());
+
+        // Read-modify-write writes the unmodified value first
+        cpu.tick_for(5).expect("Tick failed");
+        assert_eq!(cpu.addr[0x1000], 0x41);
+        assert_eq!(
+            cpu.steps.done[3..],
+            [access(0x1000, 0x41, false), access(0x1000, 0x41, true)]
+        );
+        assert!(!cpu.N);
+        cpu.tick().expect("Tick failed");
+        assert_eq!(cpu.addr[0x1000], 0x42);
+        assert_eq!(cpu.PC, 0x0008);
+        assert_eq!(cpu.cycles_get(), 2 + 5 + 6);
+    }
+
+    #[test]
+    fn test_stepped_mode_matches_fast() {
+        let mut memory = vec![0; 0x10000];
+        memory[0x0200..0x0203].copy_from_slice(&[0x00, 0xF0, 0x20]);
+        memory[0x00F0..0x00F2].copy_from_slice(&[0xF0, 0x30]);
+        memory[0xFFFA..].copy_from_slice(&[0x00, 0x40, 0x00, 0x02, 0x00, 0x40]);
+
+        for variant in [Variant::Nmos6502, Variant::Wdc65C02] {
+            for op in 0..=255 {
+                for flags in [false, true] {
+                    memory[0x0200] = op;
+                    let mut cpus = [0, 1].map(|_| {
+                        let mut cpu = Cpu::with_variant(Memory::from(&memory[..]), variant);
+                        cpu.reset().expect("Reset failed");
+                        (cpu.X, cpu.Y) = (0x20, 0x20);
+                        (cpu.C, cpu.Z, cpu.D, cpu.V, cpu.N) = (flags, flags, flags, flags, flags);
+                        cpu
+                    });
+                    let [fast, stepped] = &mut cpus;
+
+                    // JAM and STP never finish
+                    if fast.tick().is_err() {
+                        continue;
+                    }
+
+                    stepped.mode_set(Mode::Stepped);
+                    stepped.tick().expect("Tick failed");
+                    while !stepped.steps.done.is_empty() {
+                        stepped.tick().expect("Tick failed");
+                    }
+
+                    let name = format!("{variant:?} {op:#04x} {flags}");
+                    assert_eq!(stepped.cycles_get(), fast.cycles_get(), "{name}");
+                    assert_eq!(stepped.state_get(), fast.state_get(), "{name}");
+                    for addr in 0..=Word::MAX {
+                        assert_eq!(stepped.addr[addr], fast.addr[addr], "{name} {addr:#06x}");
+                    }
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,26 @@
+use crate::line::Line;
+
+/// The RDY, SO and SYNC pins
+#[derive(Debug, Default)]
+pub struct Pins {
+    /// Ready, pulled low to stall the processor
+    pub rdy: Line,
+    /// Set overflow, pulled low to set the overflow flag
+    pub so: Line,
+
+    /// SO as sampled on the previous cycle, to detect edges
+    so_prev: bool,
+
+    /// High on cycles that fetch an op code
+    pub sync: bool,
+}
+
+impl Pins {
+    /// Sample SO at the start of a cycle, returning true on a falling edge
+    pub fn set_overflow(&mut self) -> bool {
+        let so = self.so.asserted();
+        let edge = so && !self.so_prev;
+        self.so_prev = so;
+        edge
+    }
+}
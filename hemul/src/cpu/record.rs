@@ -0,0 +1,77 @@
+use super::instructions::{AddressMode, OpCode};
+use crate::{Byte, Word};
+
+/// A single read or write on the bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Access {
+    pub addr: Word,
+    pub data: Byte,
+    pub write: bool,
+}
+
+/// What a single instruction did, as returned by [`super::Cpu::step`]. Interrupt and reset
+/// sequences run like a BRK that the processor forces in, so they show up as one with the
+/// vector they went through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Address of the op code
+    pub pc: Word,
+    /// The op code, 0x00 for interrupt and reset sequences
+    pub opcode: Byte,
+    /// Decoded instruction
+    pub op: OpCode,
+    /// Address mode of the instruction
+    pub mode: AddressMode,
+    /// Operand bytes that follow the op code
+    pub operands: Vec<Byte>,
+    /// Address the instruction operated on, or jumped or branched to
+    pub addr: Option<Word>,
+    /// Vector that an interrupt or reset sequence went through
+    pub interrupt: Option<Word>,
+    /// Clock cycles used
+    pub cycles: u64,
+    /// Bus reads and writes in the order they happened
+    pub accesses: Vec<Access>,
+}
+
+/// What the Cpu notes about the instruction that runs while [`super::Cpu::step`] watches
+#[derive(Debug, Default)]
+pub(super) struct Trace {
+    /// Address of the op code
+    pub pc: Word,
+    /// Op code and decoded instruction, once it has been fetched
+    pub op: Option<(Byte, OpCode, AddressMode)>,
+    /// Address the instruction operated on
+    pub addr: Option<Word>,
+    /// Vector of an interrupt or reset sequence
+    pub interrupt: Option<Word>,
+    /// Bus accesses done so far
+    pub accesses: Vec<Access>,
+}
+
+impl Trace {
+    /// Turn the trace into a record, if an instruction ran
+    pub fn finish(self, cycles: u64) -> Option<Record> {
+        let (opcode, op, mode) = self.op?;
+        let operands = (1..=mode.operands())
+            .filter_map(|i| {
+                let addr = self.pc.wrapping_add(Word::from(i));
+                self.accesses
+                    .iter()
+                    .find(|access| !access.write && access.addr == addr)
+                    .map(|access| access.data)
+            })
+            .collect();
+        Some(Record {
+            pc: self.pc,
+            opcode,
+            op,
+            mode,
+            operands,
+            addr: self.addr,
+            interrupt: self.interrupt,
+            cycles,
+            accesses: self.accesses,
+        })
+    }
+}
@@ -1,8 +1,7 @@
-use crate::{Addressable, Snapshottable};
+use crate::{Device, Error, Snapshottable};
 
 use super::{Byte, Cpu, PFlag, Word};
 use std::{
-    error::Error,
     io::prelude::*,
     process::{Command, Stdio},
 };
@@ -32,8 +31,6 @@ pub struct Snapshot {
     pub I: PFlag,
     /// Decimal Mode
     pub D: PFlag,
-    /// Break Command
-    pub B: PFlag,
     /// Overflow Flag
     pub V: PFlag,
     /// Negative Flag
@@ -42,22 +39,21 @@ pub struct Snapshot {
 
 impl std::fmt::Debug for Snapshot {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "PC\t\tSP\tA\tX\tY\tCZIDBVN")?;
+        writeln!(f, "PC\t\tSP\tA\tX\tY\tNV-BDIZC")?;
         write!(
             f,
-            "{:#06x}\t{:#04x}\t{:#04x}\t{:#04x}\t{:#04x}\t{}{}{}{}{}{}{}",
+            "{:#06x}\t{:#04x}\t{:#04x}\t{:#04x}\t{:#04x}\t{}{}--{}{}{}{}",
             self.PC,
             self.SP,
             self.A,
             self.X,
             self.Y,
-            i32::from(self.C),
-            i32::from(self.Z),
-            i32::from(self.I),
-            i32::from(self.D),
-            i32::from(self.B),
-            i32::from(self.V),
             i32::from(self.N),
+            i32::from(self.V),
+            i32::from(self.D),
+            i32::from(self.I),
+            i32::from(self.Z),
+            i32::from(self.C),
         )?;
         write!(f, "\n\n")?;
 
@@ -89,11 +85,11 @@ impl std::fmt::Debug for Snapshot {
 
 impl<T> Snapshottable for Cpu<T>
 where
-    T: Addressable + Snapshottable<Snapshot = Vec<u8>>,
+    T: Device + Snapshottable<Snapshot = Vec<u8>>,
 {
     type Snapshot = Snapshot;
 
-    fn snapshot(&self) -> Result<Self::Snapshot, Box<dyn Error>> {
+    fn snapshot(&self) -> Result<Self::Snapshot, Error> {
         Ok(Snapshot {
             dump: self.addr.snapshot()?,
 
@@ -108,7 +104,6 @@ where
             Z: self.Z,
             I: self.I,
             D: self.D,
-            B: self.B,
             V: self.V,
             N: self.N,
         })
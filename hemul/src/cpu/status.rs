@@ -0,0 +1,144 @@
+use crate::Byte;
+
+// Status register flag bits, NV-BDIZC
+const C_FLAG: Byte = 0b0000_0001; // Carry
+const Z_FLAG: Byte = 0b0000_0010; // Zero
+const I_FLAG: Byte = 0b0000_0100; // Interrupt Disable
+const D_FLAG: Byte = 0b0000_1000; // Decimal Mode
+const B_FLAG: Byte = 0b0001_0000; // Break Command, only on the stack
+const U_FLAG: Byte = 0b0010_0000; // Unused, always set on the stack
+const V_FLAG: Byte = 0b0100_0000; // Overflow
+const N_FLAG: Byte = 0b1000_0000; // Negative
+
+/// The processor status register. Only the six flags exist in the processor, the break and
+/// unused bits only show up when the register is pushed to the stack.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Status(Byte);
+
+impl Status {
+    fn flag_get(self, flag: Byte) -> bool {
+        self.0 & flag > 0
+    }
+
+    fn flag_set(&mut self, flag: Byte, value: bool) {
+        if value {
+            self.0 |= flag;
+        } else {
+            self.0 &= !flag;
+        }
+    }
+
+    /// Carry Flag
+    pub fn carry_get(self) -> bool {
+        self.flag_get(C_FLAG)
+    }
+
+    /// Set the carry flag
+    pub fn carry_set(&mut self, value: bool) {
+        self.flag_set(C_FLAG, value);
+    }
+
+    /// Zero Flag
+    pub fn zero_get(self) -> bool {
+        self.flag_get(Z_FLAG)
+    }
+
+    /// Set the zero flag
+    pub fn zero_set(&mut self, value: bool) {
+        self.flag_set(Z_FLAG, value);
+    }
+
+    /// Interrupt Disable
+    pub fn interrupt_disable_get(self) -> bool {
+        self.flag_get(I_FLAG)
+    }
+
+    /// Set the interrupt disable flag
+    pub fn interrupt_disable_set(&mut self, value: bool) {
+        self.flag_set(I_FLAG, value);
+    }
+
+    /// Decimal Mode
+    pub fn decimal_get(self) -> bool {
+        self.flag_get(D_FLAG)
+    }
+
+    /// Set the decimal mode flag
+    pub fn decimal_set(&mut self, value: bool) {
+        self.flag_set(D_FLAG, value);
+    }
+
+    /// Overflow Flag
+    pub fn overflow_get(self) -> bool {
+        self.flag_get(V_FLAG)
+    }
+
+    /// Set the overflow flag
+    pub fn overflow_set(&mut self, value: bool) {
+        self.flag_set(V_FLAG, value);
+    }
+
+    /// Negative Flag
+    pub fn negative_get(self) -> bool {
+        self.flag_get(N_FLAG)
+    }
+
+    /// Set the negative flag
+    pub fn negative_set(&mut self, value: bool) {
+        self.flag_set(N_FLAG, value);
+    }
+
+    /// The byte that is pushed to the stack, with the break flag set for BRK and PHP
+    pub fn pushed(self, brk: bool) -> Byte {
+        if brk {
+            self.0 | U_FLAG | B_FLAG
+        } else {
+            self.0 | U_FLAG
+        }
+    }
+}
+
+impl From<Byte> for Status {
+    /// Status as pulled from the stack, ignoring the break and unused bits
+    fn from(value: Byte) -> Self {
+        Self(value & !(B_FLAG | U_FLAG))
+    }
+}
+
+impl From<Status> for Byte {
+    /// Status as an interrupt pushes it, with the unused bit set and the break flag clear
+    fn from(value: Status) -> Self {
+        value.pushed(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        let mut status = Status::default();
+        status.carry_set(true);
+        status.negative_set(true);
+        assert!(status.carry_get());
+        assert!(status.negative_get());
+        assert!(!status.zero_get());
+        assert_eq!(Byte::from(status), 0b1010_0001);
+
+        status.carry_set(false);
+        assert!(!status.carry_get());
+        assert_eq!(Byte::from(status), 0b1010_0000);
+    }
+
+    #[test]
+    fn test_stack_byte() {
+        let status = Status::from(0xFF);
+        assert!(status.interrupt_disable_get());
+        assert!(status.decimal_get());
+        assert!(status.overflow_get());
+        assert_eq!(status.pushed(false), 0b1110_1111);
+        assert_eq!(status.pushed(true), 0xFF);
+        assert_eq!(Status::from(0b0011_0000), Status::default());
+    }
+}
@@ -0,0 +1,540 @@
+use crate::Byte;
+
+/// The 65C816 instruction set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    /// ADC - Add with Carry
+    Adc,
+    /// AND - Logical AND
+    And,
+    /// ASL - Arithmetic Shift Left
+    Asl,
+    /// BCC - Branch if Carry Clear
+    Bcc,
+    /// BCS - Branch if Carry Set
+    Bcs,
+    /// BEQ - Branch if Equal
+    Beq,
+    /// BIT - Bit Test
+    Bit,
+    /// BMI - Branch if Minus
+    Bmi,
+    /// BNE - Branch if Not Equal
+    Bne,
+    /// BPL - Branch if Positive
+    Bpl,
+    /// BRA - Branch Always
+    Bra,
+    /// BRK - Force Interrupt, skipping a signature byte
+    Brk,
+    /// BRL - Branch Always Long, with a 16 bit offset
+    Brl,
+    /// BVC - Branch if Overflow Clear
+    Bvc,
+    /// BVS - Branch if Overflow Set
+    Bvs,
+    /// CLC - Clear Carry Flag
+    Clc,
+    /// CLD - Clear Decimal Mode
+    Cld,
+    /// CLI - Clear Interrupt Disable
+    Cli,
+    /// CLV - Clear Overflow Flag
+    Clv,
+    /// CMP - Compare Accumulator
+    Cmp,
+    /// COP - Co-Processor Interrupt, skipping a signature byte
+    Cop,
+    /// CPX - Compare X Register
+    Cpx,
+    /// CPY - Compare Y Register
+    Cpy,
+    /// DEC - Decrement Memory or Accumulator
+    Dec,
+    /// DEX - Decrement X Register
+    Dex,
+    /// DEY - Decrement Y Register
+    Dey,
+    /// EOR - Exclusive OR
+    Eor,
+    /// INC - Increment Memory or Accumulator
+    Inc,
+    /// INX - Increment X Register
+    Inx,
+    /// INY - Increment Y Register
+    Iny,
+    /// JML - Jump Long, to another bank
+    Jml,
+    /// JMP - Jump, within the program bank
+    Jmp,
+    /// JSL - Jump to Subroutine Long, pushing the program bank too
+    Jsl,
+    /// JSR - Jump to Subroutine, within the program bank
+    Jsr,
+    /// LDA - Load Accumulator
+    Lda,
+    /// LDX - Load X Register
+    Ldx,
+    /// LDY - Load Y Register
+    Ldy,
+    /// LSR - Logical Shift Right
+    Lsr,
+    /// MVN - Block Move Next, copying a byte and incrementing X and Y until C wraps around
+    Mvn,
+    /// MVP - Block Move Previous, copying a byte and decrementing X and Y until C wraps around
+    Mvp,
+    /// NOP - No Operation
+    Nop,
+    /// ORA - Logical Inclusive OR
+    Ora,
+    /// PEA - Push Effective Absolute Address
+    Pea,
+    /// PEI - Push Effective Indirect Address
+    Pei,
+    /// PER - Push Effective PC Relative Address
+    Per,
+    /// PHA - Push Accumulator
+    Pha,
+    /// PHB - Push Data Bank Register
+    Phb,
+    /// PHD - Push Direct Page Register
+    Phd,
+    /// PHK - Push Program Bank Register
+    Phk,
+    /// PHP - Push Processor Status
+    Php,
+    /// PHX - Push X Register
+    Phx,
+    /// PHY - Push Y Register
+    Phy,
+    /// PLA - Pull Accumulator
+    Pla,
+    /// PLB - Pull Data Bank Register
+    Plb,
+    /// PLD - Pull Direct Page Register
+    Pld,
+    /// PLP - Pull Processor Status
+    Plp,
+    /// PLX - Pull X Register
+    Plx,
+    /// PLY - Pull Y Register
+    Ply,
+    /// REP - Reset Status Bits that are set in the operand
+    Rep,
+    /// ROL - Rotate Left
+    Rol,
+    /// ROR - Rotate Right
+    Ror,
+    /// RTI - Return from Interrupt
+    Rti,
+    /// RTL - Return from Subroutine Long
+    Rtl,
+    /// RTS - Return from Subroutine
+    Rts,
+    /// SBC - Subtract with Carry
+    Sbc,
+    /// SEC - Set Carry Flag
+    Sec,
+    /// SED - Set Decimal Flag
+    Sed,
+    /// SEI - Set Interrupt Disable
+    Sei,
+    /// SEP - Set Status Bits that are set in the operand
+    Sep,
+    /// STA - Store Accumulator
+    Sta,
+    /// STP - Stop the Processor until reset
+    Stp,
+    /// STX - Store X Register
+    Stx,
+    /// STY - Store Y Register
+    Sty,
+    /// STZ - Store Zero
+    Stz,
+    /// TAX - Transfer Accumulator to X
+    Tax,
+    /// TAY - Transfer Accumulator to Y
+    Tay,
+    /// TCD - Transfer 16 bit Accumulator to Direct Page Register
+    Tcd,
+    /// TCS - Transfer 16 bit Accumulator to Stack Pointer
+    Tcs,
+    /// TDC - Transfer Direct Page Register to 16 bit Accumulator
+    Tdc,
+    /// TRB - Test and Reset Memory Bits
+    Trb,
+    /// TSB - Test and Set Memory Bits
+    Tsb,
+    /// TSC - Transfer Stack Pointer to 16 bit Accumulator
+    Tsc,
+    /// TSX - Transfer Stack Pointer to X
+    Tsx,
+    /// TXA - Transfer X to Accumulator
+    Txa,
+    /// TXS - Transfer X to Stack Pointer
+    Txs,
+    /// TXY - Transfer X to Y
+    Txy,
+    /// TYA - Transfer Y to Accumulator
+    Tya,
+    /// TYX - Transfer Y to X
+    Tyx,
+    /// WAI - Wait for Interrupt
+    Wai,
+    /// WDM - Reserved for future expansion, a two byte NOP
+    Wdm,
+    /// XBA - Exchange the B and A Accumulators
+    Xba,
+    /// XCE - Exchange Carry and Emulation Flags
+    Xce,
+}
+
+/// How the 65C816 addresses memory. Absolute addresses are in the data bank, direct page and stack
+/// relative addresses in bank 0, and long addresses give the bank themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressMode {
+    /// No operand, or a signature byte that is skipped
+    Implied,
+    /// Operates on the accumulator
+    Accumulator,
+    /// Operand of one byte, or two for a 16 bit register
+    Immediate,
+    /// 8 bit offset from the PC
+    Relative,
+    /// 16 bit offset from the PC
+    RelativeLong,
+    /// `a`, 16 bit address in the data bank, or the program bank for jumps
+    Absolute,
+    /// `a,x`
+    AbsoluteX,
+    /// `a,y`
+    AbsoluteY,
+    /// `al`, 24 bit address
+    AbsoluteLong,
+    /// `al,x`
+    AbsoluteLongX,
+    /// `(a)`, jump through a pointer in bank 0
+    AbsoluteIndirect,
+    /// `(a,x)`, jump through a pointer in the program bank
+    AbsoluteIndexedIndirect,
+    /// `[a]`, jump through a 24 bit pointer in bank 0
+    AbsoluteIndirectLong,
+    /// `d`, offset from the direct page register
+    Direct,
+    /// `d,x`
+    DirectX,
+    /// `d,y`
+    DirectY,
+    /// `(d)`, pointer in the direct page to the data bank
+    DirectIndirect,
+    /// `[d]`, 24 bit pointer in the direct page
+    DirectIndirectLong,
+    /// `(d,x)`
+    DirectIndexedIndirect,
+    /// `(d),y`
+    DirectIndirectIndexed,
+    /// `[d],y`
+    DirectIndirectLongIndexed,
+    /// `d,s`, offset from the stack pointer
+    StackRelative,
+    /// `(d,s),y`
+    StackRelativeIndirectIndexed,
+    /// `xyc`, destination and source bank of a block move
+    BlockMove,
+}
+
+/// Cycles an instruction takes with 8 bit registers, in emulation mode and with the direct page
+/// register on a page boundary
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Cycles {
+    /// The instruction always takes constant time
+    Constant(u8),
+
+    /// The instruction takes +1 if indexing crosses a page or the index registers are 16 bit
+    Page(u8),
+
+    /// The instruction takes +1 if the branch is taken, +2 if to a new page in emulation mode
+    Branch(u8),
+}
+
+/// Container for instruction, address mode and number of cycles used for a given op code
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Op(pub OpCode, pub AddressMode, pub Cycles);
+
+macro_rules! op {
+    ($op_code:ident, $address_mode:ident, $cycles: literal) => {
+        Op(
+            OpCode::$op_code,
+            AddressMode::$address_mode,
+            Cycles::Constant($cycles),
+        )
+    };
+    ($op_code:ident, $address_mode:ident, $cycles: expr) => {
+        Op(OpCode::$op_code, AddressMode::$address_mode, $cycles)
+    };
+}
+
+impl From<Byte> for Op {
+    /// Every op code of the 65C816 is an instruction
+    #[allow(clippy::too_many_lines)]
+    fn from(value: Byte) -> Self {
+        match value {
+            0x00 => op!(Brk, Implied, 7),
+            0x01 => op!(Ora, DirectIndexedIndirect, 6),
+            0x02 => op!(Cop, Implied, 7),
+            0x03 => op!(Ora, StackRelative, 4),
+            0x04 => op!(Tsb, Direct, 5),
+            0x05 => op!(Ora, Direct, 3),
+            0x06 => op!(Asl, Direct, 5),
+            0x07 => op!(Ora, DirectIndirectLong, 6),
+            0x08 => op!(Php, Implied, 3),
+            0x09 => op!(Ora, Immediate, 2),
+            0x0A => op!(Asl, Accumulator, 2),
+            0x0B => op!(Phd, Implied, 4),
+            0x0C => op!(Tsb, Absolute, 6),
+            0x0D => op!(Ora, Absolute, 4),
+            0x0E => op!(Asl, Absolute, 6),
+            0x0F => op!(Ora, AbsoluteLong, 5),
+            0x10 => op!(Bpl, Relative, Cycles::Branch(2)),
+            0x11 => op!(Ora, DirectIndirectIndexed, Cycles::Page(5)),
+            0x12 => op!(Ora, DirectIndirect, 5),
+            0x13 => op!(Ora, StackRelativeIndirectIndexed, 7),
+            0x14 => op!(Trb, Direct, 5),
+            0x15 => op!(Ora, DirectX, 4),
+            0x16 => op!(Asl, DirectX, 6),
+            0x17 => op!(Ora, DirectIndirectLongIndexed, 6),
+            0x18 => op!(Clc, Implied, 2),
+            0x19 => op!(Ora, AbsoluteY, Cycles::Page(4)),
+            0x1A => op!(Inc, Accumulator, 2),
+            0x1B => op!(Tcs, Implied, 2),
+            0x1C => op!(Trb, Absolute, 6),
+            0x1D => op!(Ora, AbsoluteX, Cycles::Page(4)),
+            0x1E => op!(Asl, AbsoluteX, 7),
+            0x1F => op!(Ora, AbsoluteLongX, 5),
+            0x20 => op!(Jsr, Absolute, 6),
+            0x21 => op!(And, DirectIndexedIndirect, 6),
+            0x22 => op!(Jsl, AbsoluteLong, 8),
+            0x23 => op!(And, StackRelative, 4),
+            0x24 => op!(Bit, Direct, 3),
+            0x25 => op!(And, Direct, 3),
+            0x26 => op!(Rol, Direct, 5),
+            0x27 => op!(And, DirectIndirectLong, 6),
+            0x28 => op!(Plp, Implied, 4),
+            0x29 => op!(And, Immediate, 2),
+            0x2A => op!(Rol, Accumulator, 2),
+            0x2B => op!(Pld, Implied, 5),
+            0x2C => op!(Bit, Absolute, 4),
+            0x2D => op!(And, Absolute, 4),
+            0x2E => op!(Rol, Absolute, 6),
+            0x2F => op!(And, AbsoluteLong, 5),
+            0x30 => op!(Bmi, Relative, Cycles::Branch(2)),
+            0x31 => op!(And, DirectIndirectIndexed, Cycles::Page(5)),
+            0x32 => op!(And, DirectIndirect, 5),
+            0x33 => op!(And, StackRelativeIndirectIndexed, 7),
+            0x34 => op!(Bit, DirectX, 4),
+            0x35 => op!(And, DirectX, 4),
+            0x36 => op!(Rol, DirectX, 6),
+            0x37 => op!(And, DirectIndirectLongIndexed, 6),
+            0x38 => op!(Sec, Implied, 2),
+            0x39 => op!(And, AbsoluteY, Cycles::Page(4)),
+            0x3A => op!(Dec, Accumulator, 2),
+            0x3B => op!(Tsc, Implied, 2),
+            0x3C => op!(Bit, AbsoluteX, Cycles::Page(4)),
+            0x3D => op!(And, AbsoluteX, Cycles::Page(4)),
+            0x3E => op!(Rol, AbsoluteX, 7),
+            0x3F => op!(And, AbsoluteLongX, 5),
+            0x40 => op!(Rti, Implied, 6),
+            0x41 => op!(Eor, DirectIndexedIndirect, 6),
+            0x42 => op!(Wdm, Implied, 2),
+            0x43 => op!(Eor, StackRelative, 4),
+            0x44 => op!(Mvp, BlockMove, 7),
+            0x45 => op!(Eor, Direct, 3),
+            0x46 => op!(Lsr, Direct, 5),
+            0x47 => op!(Eor, DirectIndirectLong, 6),
+            0x48 => op!(Pha, Implied, 3),
+            0x49 => op!(Eor, Immediate, 2),
+            0x4A => op!(Lsr, Accumulator, 2),
+            0x4B => op!(Phk, Implied, 3),
+            0x4C => op!(Jmp, Absolute, 3),
+            0x4D => op!(Eor, Absolute, 4),
+            0x4E => op!(Lsr, Absolute, 6),
+            0x4F => op!(Eor, AbsoluteLong, 5),
+            0x50 => op!(Bvc, Relative, Cycles::Branch(2)),
+            0x51 => op!(Eor, DirectIndirectIndexed, Cycles::Page(5)),
+            0x52 => op!(Eor, DirectIndirect, 5),
+            0x53 => op!(Eor, StackRelativeIndirectIndexed, 7),
+            0x54 => op!(Mvn, BlockMove, 7),
+            0x55 => op!(Eor, DirectX, 4),
+            0x56 => op!(Lsr, DirectX, 6),
+            0x57 => op!(Eor, DirectIndirectLongIndexed, 6),
+            0x58 => op!(Cli, Implied, 2),
+            0x59 => op!(Eor, AbsoluteY, Cycles::Page(4)),
+            0x5A => op!(Phy, Implied, 3),
+            0x5B => op!(Tcd, Implied, 2),
+            0x5C => op!(Jml, AbsoluteLong, 4),
+            0x5D => op!(Eor, AbsoluteX, Cycles::Page(4)),
+            0x5E => op!(Lsr, AbsoluteX, 7),
+            0x5F => op!(Eor, AbsoluteLongX, 5),
+            0x60 => op!(Rts, Implied, 6),
+            0x61 => op!(Adc, DirectIndexedIndirect, 6),
+            0x62 => op!(Per, RelativeLong, 6),
+            0x63 => op!(Adc, StackRelative, 4),
+            0x64 => op!(Stz, Direct, 3),
+            0x65 => op!(Adc, Direct, 3),
+            0x66 => op!(Ror, Direct, 5),
+            0x67 => op!(Adc, DirectIndirectLong, 6),
+            0x68 => op!(Pla, Implied, 4),
+            0x69 => op!(Adc, Immediate, 2),
+            0x6A => op!(Ror, Accumulator, 2),
+            0x6B => op!(Rtl, Implied, 6),
+            0x6C => op!(Jmp, AbsoluteIndirect, 5),
+            0x6D => op!(Adc, Absolute, 4),
+            0x6E => op!(Ror, Absolute, 6),
+            0x6F => op!(Adc, AbsoluteLong, 5),
+            0x70 => op!(Bvs, Relative, Cycles::Branch(2)),
+            0x71 => op!(Adc, DirectIndirectIndexed, Cycles::Page(5)),
+            0x72 => op!(Adc, DirectIndirect, 5),
+            0x73 => op!(Adc, StackRelativeIndirectIndexed, 7),
+            0x74 => op!(Stz, DirectX, 4),
+            0x75 => op!(Adc, DirectX, 4),
+            0x76 => op!(Ror, DirectX, 6),
+            0x77 => op!(Adc, DirectIndirectLongIndexed, 6),
+            0x78 => op!(Sei, Implied, 2),
+            0x79 => op!(Adc, AbsoluteY, Cycles::Page(4)),
+            0x7A => op!(Ply, Implied, 4),
+            0x7B => op!(Tdc, Implied, 2),
+            0x7C => op!(Jmp, AbsoluteIndexedIndirect, 6),
+            0x7D => op!(Adc, AbsoluteX, Cycles::Page(4)),
+            0x7E => op!(Ror, AbsoluteX, 7),
+            0x7F => op!(Adc, AbsoluteLongX, 5),
+            0x80 => op!(Bra, Relative, Cycles::Branch(2)),
+            0x81 => op!(Sta, DirectIndexedIndirect, 6),
+            0x82 => op!(Brl, RelativeLong, 4),
+            0x83 => op!(Sta, StackRelative, 4),
+            0x84 => op!(Sty, Direct, 3),
+            0x85 => op!(Sta, Direct, 3),
+            0x86 => op!(Stx, Direct, 3),
+            0x87 => op!(Sta, DirectIndirectLong, 6),
+            0x88 => op!(Dey, Implied, 2),
+            0x89 => op!(Bit, Immediate, 2),
+            0x8A => op!(Txa, Implied, 2),
+            0x8B => op!(Phb, Implied, 3),
+            0x8C => op!(Sty, Absolute, 4),
+            0x8D => op!(Sta, Absolute, 4),
+            0x8E => op!(Stx, Absolute, 4),
+            0x8F => op!(Sta, AbsoluteLong, 5),
+            0x90 => op!(Bcc, Relative, Cycles::Branch(2)),
+            0x91 => op!(Sta, DirectIndirectIndexed, 6),
+            0x92 => op!(Sta, DirectIndirect, 5),
+            0x93 => op!(Sta, StackRelativeIndirectIndexed, 7),
+            0x94 => op!(Sty, DirectX, 4),
+            0x95 => op!(Sta, DirectX, 4),
+            0x96 => op!(Stx, DirectY, 4),
+            0x97 => op!(Sta, DirectIndirectLongIndexed, 6),
+            0x98 => op!(Tya, Implied, 2),
+            0x99 => op!(Sta, AbsoluteY, 5),
+            0x9A => op!(Txs, Implied, 2),
+            0x9B => op!(Txy, Implied, 2),
+            0x9C => op!(Stz, Absolute, 4),
+            0x9D => op!(Sta, AbsoluteX, 5),
+            0x9E => op!(Stz, AbsoluteX, 5),
+            0x9F => op!(Sta, AbsoluteLongX, 5),
+            0xA0 => op!(Ldy, Immediate, 2),
+            0xA1 => op!(Lda, DirectIndexedIndirect, 6),
+            0xA2 => op!(Ldx, Immediate, 2),
+            0xA3 => op!(Lda, StackRelative, 4),
+            0xA4 => op!(Ldy, Direct, 3),
+            0xA5 => op!(Lda, Direct, 3),
+            0xA6 => op!(Ldx, Direct, 3),
+            0xA7 => op!(Lda, DirectIndirectLong, 6),
+            0xA8 => op!(Tay, Implied, 2),
+            0xA9 => op!(Lda, Immediate, 2),
+            0xAA => op!(Tax, Implied, 2),
+            0xAB => op!(Plb, Implied, 4),
+            0xAC => op!(Ldy, Absolute, 4),
+            0xAD => op!(Lda, Absolute, 4),
+            0xAE => op!(Ldx, Absolute, 4),
+            0xAF => op!(Lda, AbsoluteLong, 5),
+            0xB0 => op!(Bcs, Relative, Cycles::Branch(2)),
+            0xB1 => op!(Lda, DirectIndirectIndexed, Cycles::Page(5)),
+            0xB2 => op!(Lda, DirectIndirect, 5),
+            0xB3 => op!(Lda, StackRelativeIndirectIndexed, 7),
+            0xB4 => op!(Ldy, DirectX, 4),
+            0xB5 => op!(Lda, DirectX, 4),
+            0xB6 => op!(Ldx, DirectY, 4),
+            0xB7 => op!(Lda, DirectIndirectLongIndexed, 6),
+            0xB8 => op!(Clv, Implied, 2),
+            0xB9 => op!(Lda, AbsoluteY, Cycles::Page(4)),
+            0xBA => op!(Tsx, Implied, 2),
+            0xBB => op!(Tyx, Implied, 2),
+            0xBC => op!(Ldy, AbsoluteX, Cycles::Page(4)),
+            0xBD => op!(Lda, AbsoluteX, Cycles::Page(4)),
+            0xBE => op!(Ldx, AbsoluteY, Cycles::Page(4)),
+            0xBF => op!(Lda, AbsoluteLongX, 5),
+            0xC0 => op!(Cpy, Immediate, 2),
+            0xC1 => op!(Cmp, DirectIndexedIndirect, 6),
+            0xC2 => op!(Rep, Immediate, 3),
+            0xC3 => op!(Cmp, StackRelative, 4),
+            0xC4 => op!(Cpy, Direct, 3),
+            0xC5 => op!(Cmp, Direct, 3),
+            0xC6 => op!(Dec, Direct, 5),
+            0xC7 => op!(Cmp, DirectIndirectLong, 6),
+            0xC8 => op!(Iny, Implied, 2),
+            0xC9 => op!(Cmp, Immediate, 2),
+            0xCA => op!(Dex, Implied, 2),
+            0xCB => op!(Wai, Implied, 3),
+            0xCC => op!(Cpy, Absolute, 4),
+            0xCD => op!(Cmp, Absolute, 4),
+            0xCE => op!(Dec, Absolute, 6),
+            0xCF => op!(Cmp, AbsoluteLong, 5),
+            0xD0 => op!(Bne, Relative, Cycles::Branch(2)),
+            0xD1 => op!(Cmp, DirectIndirectIndexed, Cycles::Page(5)),
+            0xD2 => op!(Cmp, DirectIndirect, 5),
+            0xD3 => op!(Cmp, StackRelativeIndirectIndexed, 7),
+            0xD4 => op!(Pei, Direct, 6),
+            0xD5 => op!(Cmp, DirectX, 4),
+            0xD6 => op!(Dec, DirectX, 6),
+            0xD7 => op!(Cmp, DirectIndirectLongIndexed, 6),
+            0xD8 => op!(Cld, Implied, 2),
+            0xD9 => op!(Cmp, AbsoluteY, Cycles::Page(4)),
+            0xDA => op!(Phx, Implied, 3),
+            0xDB => op!(Stp, Implied, 3),
+            0xDC => op!(Jml, AbsoluteIndirectLong, 6),
+            0xDD => op!(Cmp, AbsoluteX, Cycles::Page(4)),
+            0xDE => op!(Dec, AbsoluteX, 7),
+            0xDF => op!(Cmp, AbsoluteLongX, 5),
+            0xE0 => op!(Cpx, Immediate, 2),
+            0xE1 => op!(Sbc, DirectIndexedIndirect, 6),
+            0xE2 => op!(Sep, Immediate, 3),
+            0xE3 => op!(Sbc, StackRelative, 4),
+            0xE4 => op!(Cpx, Direct, 3),
+            0xE5 => op!(Sbc, Direct, 3),
+            0xE6 => op!(Inc, Direct, 5),
+            0xE7 => op!(Sbc, DirectIndirectLong, 6),
+            0xE8 => op!(Inx, Implied, 2),
+            0xE9 => op!(Sbc, Immediate, 2),
+            0xEA => op!(Nop, Implied, 2),
+            0xEB => op!(Xba, Implied, 3),
+            0xEC => op!(Cpx, Absolute, 4),
+            0xED => op!(Sbc, Absolute, 4),
+            0xEE => op!(Inc, Absolute, 6),
+            0xEF => op!(Sbc, AbsoluteLong, 5),
+            0xF0 => op!(Beq, Relative, Cycles::Branch(2)),
+            0xF1 => op!(Sbc, DirectIndirectIndexed, Cycles::Page(5)),
+            0xF2 => op!(Sbc, DirectIndirect, 5),
+            0xF3 => op!(Sbc, StackRelativeIndirectIndexed, 7),
+            0xF4 => op!(Pea, Absolute, 5),
+            0xF5 => op!(Sbc, DirectX, 4),
+            0xF6 => op!(Inc, DirectX, 6),
+            0xF7 => op!(Sbc, DirectIndirectLongIndexed, 6),
+            0xF8 => op!(Sed, Implied, 2),
+            0xF9 => op!(Sbc, AbsoluteY, Cycles::Page(4)),
+            0xFA => op!(Plx, Implied, 4),
+            0xFB => op!(Xce, Implied, 2),
+            0xFC => op!(Jsr, AbsoluteIndexedIndirect, 8),
+            0xFD => op!(Sbc, AbsoluteX, Cycles::Page(4)),
+            0xFE => op!(Inc, AbsoluteX, 7),
+            0xFF => op!(Sbc, AbsoluteLongX, 5),
+        }
+    }
+}
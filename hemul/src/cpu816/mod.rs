@@ -0,0 +1,1256 @@
+pub use self::instructions::{AddressMode, OpCode};
+use self::instructions::{Cycles, Op};
+use crate::{
+    Byte, Device, Interruptible, Long, Resettable, RunState, Tickable, Word,
+    cpu::{IRQB, NMIB, RESB, alu, interrupt::Interrupts},
+    line::Line,
+};
+use thiserror::Error;
+
+mod instructions;
+
+// Status register flag bits, NVMXDIZC. In emulation mode M is always set and X is the break flag.
+const C_FLAG: Byte = 0b0000_0001; // Carry
+const Z_FLAG: Byte = 0b0000_0010; // Zero
+const I_FLAG: Byte = 0b0000_0100; // Interrupt Disable
+const D_FLAG: Byte = 0b0000_1000; // Decimal Mode
+const X_FLAG: Byte = 0b0001_0000; // 8 bit Index Registers
+const M_FLAG: Byte = 0b0010_0000; // 8 bit Accumulator
+const V_FLAG: Byte = 0b0100_0000; // Overflow
+const N_FLAG: Byte = 0b1000_0000; // Negative
+
+// Interrupt vectors in bank 0. Emulation mode uses the vectors of the 6502, with BRK sharing the
+// IRQ vector, and native mode has its own.
+pub const COP_NATIVE: Word = 0xFFE4;
+pub const BRK_NATIVE: Word = 0xFFE6;
+pub const ABORT_NATIVE: Word = 0xFFE8;
+pub const NMI_NATIVE: Word = 0xFFEA;
+pub const IRQ_NATIVE: Word = 0xFFEE;
+pub const COP_EMULATION: Word = 0xFFF4;
+pub const ABORT_EMULATION: Word = 0xFFF8;
+pub const NMI_EMULATION: Word = NMIB;
+pub const RESET: Word = RESB;
+pub const IRQ_EMULATION: Word = IRQB;
+
+/// Highest address on the 24 bit bus
+const LONG_MAX: Long = 0x00FF_FFFF;
+
+/// 24 bit address from a bank and an address in it
+fn long(bank: Byte, addr: Word) -> Long {
+    let [lo, hi] = addr.to_le_bytes();
+    Long::from_le_bytes([lo, hi, bank, 0])
+}
+
+/// Bank and the address in it of a 24 bit address
+fn split(addr: Long) -> (Byte, Word) {
+    let [lo, hi, bank, _] = addr.to_le_bytes();
+    (bank, Word::from_le_bytes([lo, hi]))
+}
+
+/// Address of an operand. Direct page and stack addresses wrap around in bank 0, the others carry
+/// into the next bank.
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    addr: Long,
+    bank0: bool,
+}
+
+impl Target {
+    fn long(addr: Long) -> Self {
+        Self {
+            addr: addr & LONG_MAX,
+            bank0: false,
+        }
+    }
+
+    fn bank0(addr: Word) -> Self {
+        Self {
+            addr: Long::from(addr),
+            bank0: true,
+        }
+    }
+
+    /// Address of the next byte of the operand
+    fn next(self) -> Self {
+        if self.bank0 {
+            Self::bank0(split(self.addr).1.wrapping_add(1))
+        } else {
+            Self::long(self.addr + 1)
+        }
+    }
+}
+
+/// Why the 65C816 failed, with the 24 bit address and op code of the instruction that failed
+#[allow(clippy::module_name_repetitions)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Cpu816Error {
+    /// The op code is missing if the fault hit while fetching it or in an interrupt sequence
+    #[error("bus fault in instruction at `{pc:#08x}`: memory location out of bounds `{addr:#08x}`")]
+    OutOfBounds {
+        pc: Long,
+        opcode: Option<Byte>,
+        addr: Long,
+    },
+
+    #[error("bus fault in instruction at `{pc:#08x}`: memory location is read only `{addr:#08x}`")]
+    ReadOnly {
+        pc: Long,
+        opcode: Option<Byte>,
+        addr: Long,
+    },
+
+    #[error("invalid address mode for opcode `{opcode:#04x}` at `{pc:#08x}`")]
+    InvalidAddressMode { pc: Long, opcode: Byte },
+
+    #[error("endless loop, no NOP reached in {0} ticks")]
+    EndlessLoop(usize),
+}
+
+/// The WDC 65C816, with 16 bit registers and a 24 bit bus. It starts in emulation mode, where it
+/// runs like a 65C02, and XCE switches it to native mode. Each tick runs a whole instruction.
+#[allow(non_snake_case)]
+pub struct Cpu816<T: Device<Long>> {
+    addr: T,
+
+    /// Program Counter
+    PC: Word,
+    /// Program Bank Register
+    PBR: Byte,
+    /// Data Bank Register
+    DBR: Byte,
+    /// Direct Page Register
+    D: Word,
+    /// Stack Pointer
+    S: Word,
+
+    /// Accumulator, B in the high byte
+    A: Word,
+    /// Index Register X
+    X: Word,
+    /// Index Register Y
+    Y: Word,
+
+    /// Processor Status, NVMXDIZC
+    P: Byte,
+    /// Emulation Mode
+    E: bool,
+
+    /// Whether the Cpu runs, waits for an interrupt or is stopped
+    run: RunState,
+
+    /// IRQ and NMI lines
+    interrupts: Interrupts,
+
+    /// Every op code decoded
+    decoder: [Op; 256],
+
+    /// Cycles the current instruction takes on top of what it takes at the least
+    extra: u8,
+
+    /// Set while the instruction that runs is not an interrupt or reset sequence, or a block
+    /// move that goes on with the next byte
+    retiring: bool,
+
+    /// Clock cycles used since reset
+    cycles: u64,
+
+    /// Instructions retired since reset
+    instructions: u64,
+
+    /// Address of the current instruction, or of the interrupt sequence
+    op_pc: Long,
+
+    /// Op code of the current instruction, once it has been fetched
+    opcode: Option<Byte>,
+}
+
+impl<T> Cpu816<T>
+where
+    T: Device<Long>,
+{
+    pub fn new(addr: T) -> Self {
+        Self {
+            addr,
+
+            PC: 0,
+            PBR: 0,
+            DBR: 0,
+            D: 0,
+            S: 0x0100,
+
+            A: 0,
+            X: 0,
+            Y: 0,
+
+            P: M_FLAG | X_FLAG,
+            E: true,
+
+            run: RunState::Running,
+
+            interrupts: Interrupts::default(),
+
+            decoder: std::array::from_fn(|i| Op::from(Byte::try_from(i).unwrap_or_default())),
+
+            extra: 0,
+
+            retiring: false,
+
+            cycles: 0,
+
+            instructions: 0,
+
+            op_pc: 0,
+
+            opcode: None,
+        }
+    }
+
+    /// Program Counter
+    pub fn pc_get(&self) -> Word {
+        self.PC
+    }
+
+    /// Program Bank Register
+    pub fn pbr_get(&self) -> Byte {
+        self.PBR
+    }
+
+    /// Data Bank Register
+    pub fn dbr_get(&self) -> Byte {
+        self.DBR
+    }
+
+    /// Direct Page Register
+    pub fn d_get(&self) -> Word {
+        self.D
+    }
+
+    /// Stack Pointer
+    pub fn sp_get(&self) -> Word {
+        self.S
+    }
+
+    /// Accumulator, all 16 bits of it even while it is 8 bits wide
+    pub fn a_get(&self) -> Word {
+        self.A
+    }
+
+    /// Index Register X
+    pub fn x_get(&self) -> Word {
+        self.X
+    }
+
+    /// Index Register Y
+    pub fn y_get(&self) -> Word {
+        self.Y
+    }
+
+    /// Processor Status, NVMXDIZC
+    pub fn p_get(&self) -> Byte {
+        self.P
+    }
+
+    /// Whether the processor is in emulation mode
+    pub fn emulation_get(&self) -> bool {
+        self.E
+    }
+
+    /// Clock cycles used since reset
+    pub fn cycles_get(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Instructions retired since reset, not counting interrupt and reset sequences
+    pub fn instructions_get(&self) -> u64 {
+        self.instructions
+    }
+
+    /// Tick until the next instruction is a NOP or the processor halts, giving up after 2000
+    /// ticks
+    pub fn tick_until_nop(&mut self) -> Result<(), Cpu816Error> {
+        const LIMIT: usize = 2000;
+        for _ in 0..=LIMIT {
+            if matches!(self.run, RunState::Stopped | RunState::Jammed(_)) {
+                return Ok(());
+            }
+            let pc = long(self.PBR, self.PC);
+            if !self.addr.inside_bounds(pc) {
+                return Err(Cpu816Error::OutOfBounds {
+                    pc,
+                    opcode: None,
+                    addr: pc,
+                });
+            }
+            if self.interrupts.pending.is_none() && self.addr.peek(pc) == 0xEA {
+                return Ok(());
+            }
+
+            self.clock()?;
+        }
+        Err(Cpu816Error::EndlessLoop(LIMIT))
+    }
+
+    pub fn tick_for(&mut self, count: usize) -> Result<(), Cpu816Error> {
+        for _ in 0..count {
+            self.clock()?;
+        }
+        Ok(())
+    }
+
+    fn flag(&self, flag: Byte) -> bool {
+        self.P & flag > 0
+    }
+
+    fn flag_set(&mut self, flag: Byte, value: bool) {
+        if value {
+            self.P |= flag;
+        } else {
+            self.P &= !flag;
+        }
+    }
+
+    /// Set the status register, emulation mode keeps M and X set and 8 bit index registers lose
+    /// their high byte
+    fn p_set(&mut self, p: Byte) {
+        self.P = p;
+        if self.E {
+            self.P |= M_FLAG | X_FLAG;
+        }
+        if self.flag(X_FLAG) {
+            self.X &= 0x00FF;
+            self.Y &= 0x00FF;
+        }
+    }
+
+    /// Whether the accumulator is 16 bits wide
+    fn wide_a(&self) -> bool {
+        !self.flag(M_FLAG)
+    }
+
+    /// Whether the index registers are 16 bits wide
+    fn wide_x(&self) -> bool {
+        !self.flag(X_FLAG)
+    }
+
+    /// The accumulator at its current width
+    fn acc(&self) -> Word {
+        if self.wide_a() {
+            self.A
+        } else {
+            self.A & 0x00FF
+        }
+    }
+
+    /// Set the accumulator at its current width, an 8 bit accumulator keeps B
+    fn acc_set(&mut self, value: Word) {
+        self.A = if self.wide_a() {
+            value
+        } else {
+            (self.A & 0xFF00) | (value & 0x00FF)
+        };
+    }
+
+    /// A value cut down to the width of the index registers
+    fn index(&self, value: Word) -> Word {
+        if self.wide_x() { value } else { value & 0x00FF }
+    }
+
+    /// Set the zero and negative flags for a value of 8 or 16 bits
+    fn flags_zn(&mut self, value: Word, wide: bool) {
+        let (zero, negative) = if wide {
+            (value == 0, value & 0x8000 > 0)
+        } else {
+            (value.to_le_bytes()[0] == 0, value & 0x0080 > 0)
+        };
+        self.flag_set(Z_FLAG, zero);
+        self.flag_set(N_FLAG, negative);
+    }
+
+    /// The error for a bus access that nothing answered
+    fn fault(&self, addr: Long) -> Cpu816Error {
+        Cpu816Error::OutOfBounds {
+            pc: self.op_pc,
+            opcode: self.opcode,
+            addr,
+        }
+    }
+
+    /// The current instruction has an address mode that it can not use
+    fn invalid_address_mode(&self) -> Cpu816Error {
+        Cpu816Error::InvalidAddressMode {
+            pc: self.op_pc,
+            opcode: self.opcode.unwrap_or_default(),
+        }
+    }
+
+    /// Read byte from a 24 bit address
+    fn read(&mut self, addr: Long) -> Result<Byte, Cpu816Error> {
+        let addr = addr & LONG_MAX;
+        if !self.addr.inside_bounds(addr) {
+            return Err(self.fault(addr));
+        }
+        Ok(self.addr.read(addr))
+    }
+
+    /// Write byte to a 24 bit address
+    fn write(&mut self, addr: Long, data: Byte) -> Result<(), Cpu816Error> {
+        let addr = addr & LONG_MAX;
+        if !self.addr.inside_bounds(addr) {
+            return Err(self.fault(addr));
+        }
+        if !self.addr.writable(addr) {
+            return Err(Cpu816Error::ReadOnly {
+                pc: self.op_pc,
+                opcode: self.opcode,
+                addr,
+            });
+        }
+        self.addr.write(addr, data);
+        Ok(())
+    }
+
+    /// Read a word
+    fn read_word(&mut self, target: Target) -> Result<Word, Cpu816Error> {
+        let lo = self.read(target.addr)?;
+        let hi = self.read(target.next().addr)?;
+        Ok(Word::from_le_bytes([lo, hi]))
+    }
+
+    /// Read one byte, or two for a 16 bit register which takes a cycle more
+    fn load(&mut self, target: Target, wide: bool) -> Result<Word, Cpu816Error> {
+        if wide {
+            self.extra += 1;
+            self.read_word(target)
+        } else {
+            Ok(Word::from(self.read(target.addr)?))
+        }
+    }
+
+    /// Write one byte, or two for a 16 bit register which takes a cycle more
+    fn store(&mut self, target: Target, value: Word, wide: bool) -> Result<(), Cpu816Error> {
+        let [lo, hi] = value.to_le_bytes();
+        self.write(target.addr, lo)?;
+        if wide {
+            self.extra += 1;
+            self.write(target.next().addr, hi)?;
+        }
+        Ok(())
+    }
+
+    /// Fetch byte from the program bank that the PC points to and increment the PC
+    fn fetch(&mut self) -> Result<Byte, Cpu816Error> {
+        let data = self.read(long(self.PBR, self.PC))?;
+        self.PC = self.PC.wrapping_add(1);
+        Ok(data)
+    }
+
+    /// Fetch a word that the PC points to
+    fn fetch_word(&mut self) -> Result<Word, Cpu816Error> {
+        let lo = self.fetch()?;
+        let hi = self.fetch()?;
+        Ok(Word::from_le_bytes([lo, hi]))
+    }
+
+    /// Fetch a 24 bit address that the PC points to
+    fn fetch_long(&mut self) -> Result<Long, Cpu816Error> {
+        let addr = self.fetch_word()?;
+        let bank = self.fetch()?;
+        Ok(long(bank, addr))
+    }
+
+    /// Address in the direct page plus an index, which takes a cycle more if the direct page
+    /// register is not on a page boundary. Emulation mode wraps around in the page if it is.
+    fn direct(&mut self, offset: Byte, index: Word) -> Target {
+        let [dl, dh] = self.D.to_le_bytes();
+        if dl != 0 {
+            self.extra += 1;
+        }
+        if self.E && dl == 0 {
+            Target::bank0(Word::from_le_bytes([
+                offset.wrapping_add(index.to_le_bytes()[0]),
+                dh,
+            ]))
+        } else {
+            Target::bank0(self.D.wrapping_add(Word::from(offset)).wrapping_add(index))
+        }
+    }
+
+    /// Read a pointer from the direct page. Emulation mode reads the high byte from the same page
+    /// if the direct page register is on a page boundary.
+    fn pointer(&mut self, target: Target) -> Result<Word, Cpu816Error> {
+        if self.E && self.D.to_le_bytes()[0] == 0 {
+            let [lo, hi, _, _] = target.addr.to_le_bytes();
+            let addr = self.read(target.addr)?;
+            let page = self.read(long(0, Word::from_le_bytes([lo.wrapping_add(1), hi])))?;
+            Ok(Word::from_le_bytes([addr, page]))
+        } else {
+            self.read_word(target)
+        }
+    }
+
+    /// Read a 24 bit pointer
+    fn pointer_long(&mut self, target: Target) -> Result<Long, Cpu816Error> {
+        let addr = self.read_word(target)?;
+        let bank = self.read(target.next().next().addr)?;
+        Ok(long(bank, addr))
+    }
+
+    /// Add an index register to a base address, which takes a cycle more for instructions that
+    /// read if it crosses a page or the index registers are 16 bit
+    fn indexed(&mut self, base: Long, index: Word, page: bool) -> Target {
+        let addr = (base + Long::from(index)) & LONG_MAX;
+        if page && (self.wide_x() || base >> 8 != addr >> 8) {
+            self.extra += 1;
+        }
+        Target::long(addr)
+    }
+
+    /// Fetch memory address that is referenced by some address mode
+    fn fetch_target(&mut self, mode: AddressMode, page: bool) -> Result<Target, Cpu816Error> {
+        Ok(match mode {
+            AddressMode::Absolute => Target::long(long(self.DBR, self.fetch_word()?)),
+            AddressMode::AbsoluteX => {
+                let base = long(self.DBR, self.fetch_word()?);
+                self.indexed(base, self.X, page)
+            }
+            AddressMode::AbsoluteY => {
+                let base = long(self.DBR, self.fetch_word()?);
+                self.indexed(base, self.Y, page)
+            }
+            AddressMode::AbsoluteLong => Target::long(self.fetch_long()?),
+            AddressMode::AbsoluteLongX => Target::long(self.fetch_long()? + Long::from(self.X)),
+            AddressMode::Direct => {
+                let offset = self.fetch()?;
+                self.direct(offset, 0)
+            }
+            AddressMode::DirectX => {
+                let offset = self.fetch()?;
+                self.direct(offset, self.X)
+            }
+            AddressMode::DirectY => {
+                let offset = self.fetch()?;
+                self.direct(offset, self.Y)
+            }
+            AddressMode::DirectIndirect => {
+                let offset = self.fetch()?;
+                let pointer = self.direct(offset, 0);
+                Target::long(long(self.DBR, self.pointer(pointer)?))
+            }
+            AddressMode::DirectIndirectLong => {
+                let offset = self.fetch()?;
+                let pointer = self.direct(offset, 0);
+                Target::long(self.pointer_long(pointer)?)
+            }
+            AddressMode::DirectIndexedIndirect => {
+                let offset = self.fetch()?;
+                let pointer = self.direct(offset, self.X);
+                Target::long(long(self.DBR, self.pointer(pointer)?))
+            }
+            AddressMode::DirectIndirectIndexed => {
+                let offset = self.fetch()?;
+                let pointer = self.direct(offset, 0);
+                let base = long(self.DBR, self.pointer(pointer)?);
+                self.indexed(base, self.Y, page)
+            }
+            AddressMode::DirectIndirectLongIndexed => {
+                let offset = self.fetch()?;
+                let pointer = self.direct(offset, 0);
+                Target::long(self.pointer_long(pointer)? + Long::from(self.Y))
+            }
+            AddressMode::StackRelative => {
+                let offset = self.fetch()?;
+                Target::bank0(self.S.wrapping_add(Word::from(offset)))
+            }
+            AddressMode::StackRelativeIndirectIndexed => {
+                let offset = self.fetch()?;
+                let pointer = Target::bank0(self.S.wrapping_add(Word::from(offset)));
+                let base = long(self.DBR, self.read_word(pointer)?);
+                Target::long(base + Long::from(self.Y))
+            }
+            AddressMode::Implied
+            | AddressMode::Accumulator
+            | AddressMode::Immediate
+            | AddressMode::Relative
+            | AddressMode::RelativeLong
+            | AddressMode::AbsoluteIndirect
+            | AddressMode::AbsoluteIndexedIndirect
+            | AddressMode::AbsoluteIndirectLong
+            | AddressMode::BlockMove => return Err(self.invalid_address_mode()),
+        })
+    }
+
+    /// Fetch the operand of an instruction that reads memory, one byte or two for a 16 bit
+    /// register
+    fn operand(&mut self, mode: AddressMode, page: bool, wide: bool) -> Result<Word, Cpu816Error> {
+        if mode == AddressMode::Immediate {
+            let lo = self.fetch()?;
+            if !wide {
+                return Ok(Word::from(lo));
+            }
+            self.extra += 1;
+            let hi = self.fetch()?;
+            return Ok(Word::from_le_bytes([lo, hi]));
+        }
+        let target = self.fetch_target(mode, page)?;
+        self.load(target, wide)
+    }
+
+    /// Move the stack pointer back to page 1 in emulation mode
+    fn stack_wrap(&mut self) {
+        if self.E {
+            self.S = 0x0100 | (self.S & 0x00FF);
+        }
+    }
+
+    /// Push byte onto the stack, which stays on page 1 in emulation mode
+    fn push(&mut self, data: Byte) -> Result<(), Cpu816Error> {
+        self.push_native(data)?;
+        self.stack_wrap();
+        Ok(())
+    }
+
+    /// Pop byte from the stack, which stays on page 1 in emulation mode
+    fn pull(&mut self) -> Result<Byte, Cpu816Error> {
+        self.S = self.S.wrapping_add(1);
+        self.stack_wrap();
+        self.read(Long::from(self.S))
+    }
+
+    /// Push byte onto the stack like the instructions the 65C816 added do, which leave page 1 in
+    /// emulation mode until the instruction is done
+    fn push_native(&mut self, data: Byte) -> Result<(), Cpu816Error> {
+        self.write(Long::from(self.S), data)?;
+        self.S = self.S.wrapping_sub(1);
+        Ok(())
+    }
+
+    /// Pop byte from the stack like the instructions the 65C816 added do, see
+    /// [`Cpu816::push_native`]
+    fn pull_native(&mut self) -> Result<Byte, Cpu816Error> {
+        self.S = self.S.wrapping_add(1);
+        self.read(Long::from(self.S))
+    }
+
+    /// Push a word onto the stack, high byte first
+    fn push_word(&mut self, data: Word) -> Result<(), Cpu816Error> {
+        let [lo, hi] = data.to_le_bytes();
+        self.push(hi)?;
+        self.push(lo)
+    }
+
+    /// Pop a word from the stack
+    fn pull_word(&mut self) -> Result<Word, Cpu816Error> {
+        let lo = self.pull()?;
+        let hi = self.pull()?;
+        Ok(Word::from_le_bytes([lo, hi]))
+    }
+
+    /// Push a word onto the stack like the instructions the 65C816 added do
+    fn push_word_native(&mut self, data: Word) -> Result<(), Cpu816Error> {
+        let [lo, hi] = data.to_le_bytes();
+        self.push_native(hi)?;
+        self.push_native(lo)
+    }
+
+    /// Pop a word from the stack like the instructions the 65C816 added do
+    fn pull_word_native(&mut self) -> Result<Word, Cpu816Error> {
+        let lo = self.pull_native()?;
+        let hi = self.pull_native()?;
+        Ok(Word::from_le_bytes([lo, hi]))
+    }
+
+    /// Push a register, two bytes if it is 16 bits wide which takes a cycle more
+    fn push_register(&mut self, value: Word, wide: bool) -> Result<(), Cpu816Error> {
+        if wide {
+            self.extra += 1;
+            self.push_word(value)
+        } else {
+            self.push(value.to_le_bytes()[0])
+        }
+    }
+
+    /// Pull a register, two bytes if it is 16 bits wide which takes a cycle more
+    fn pull_register(&mut self, wide: bool) -> Result<Word, Cpu816Error> {
+        if wide {
+            self.extra += 1;
+            self.pull_word()
+        } else {
+            Ok(Word::from(self.pull()?))
+        }
+    }
+
+    /// Add memory to the accumulator, a 16 bit accumulator adds the high bytes with the carry of
+    /// the low ones
+    fn add(&mut self, data: Word) {
+        self.arithmetic(data, alu::adc_cmos);
+    }
+
+    /// Subtract memory from the accumulator
+    fn sub(&mut self, data: Word) {
+        self.arithmetic(data, alu::sbc_cmos);
+    }
+
+    fn arithmetic(&mut self, data: Word, op: fn(Byte, Byte, bool, bool) -> alu::Output) {
+        let [a_lo, a_hi] = self.acc().to_le_bytes();
+        let [m_lo, m_hi] = data.to_le_bytes();
+        let decimal = self.flag(D_FLAG);
+        let lo = op(a_lo, m_lo, self.flag(C_FLAG), decimal);
+        let (result, last) = if self.wide_a() {
+            let hi = op(a_hi, m_hi, lo.c.unwrap_or_default(), decimal);
+            (Word::from_le_bytes([lo.result, hi.result]), hi)
+        } else {
+            (Word::from(lo.result), lo)
+        };
+        self.flag_set(C_FLAG, last.c.unwrap_or_default());
+        self.flag_set(V_FLAG, last.v.unwrap_or_default());
+        self.acc_set(result);
+        self.flags_zn(result, self.wide_a());
+    }
+
+    /// Compare a register with memory
+    fn compare(&mut self, register: Word, data: Word, wide: bool) {
+        self.flag_set(C_FLAG, register >= data);
+        self.flags_zn(register.wrapping_sub(data), wide);
+    }
+
+    /// Work out the result of a read-modify-write operation, setting the flags
+    fn alter(&mut self, op: OpCode, value: Word, wide: bool) -> Word {
+        let top = if wide { 0x8000 } else { 0x0080 };
+        let carry = self.flag(C_FLAG);
+        let (result, carry) = match op {
+            OpCode::Asl => (value << 1, value & top > 0),
+            OpCode::Lsr => (value >> 1, value & 1 > 0),
+            OpCode::Rol => ((value << 1) | Word::from(carry), value & top > 0),
+            OpCode::Ror => ((value >> 1) | if carry { top } else { 0 }, value & 1 > 0),
+            OpCode::Inc => (value.wrapping_add(1), carry),
+            OpCode::Dec => (value.wrapping_sub(1), carry),
+            OpCode::Tsb | OpCode::Trb => {
+                let a = self.acc();
+                self.flag_set(Z_FLAG, a & value == 0);
+                return if op == OpCode::Tsb {
+                    value | a
+                } else {
+                    value & !a
+                };
+            }
+            _ => (value, carry),
+        };
+        let result = if wide { result } else { result & 0x00FF };
+        self.flag_set(C_FLAG, carry);
+        self.flags_zn(result, wide);
+        result
+    }
+
+    /// Run a read-modify-write operation on either the accumulator or memory, writing the result
+    /// back
+    fn modify(&mut self, op: OpCode, mode: AddressMode) -> Result<(), Cpu816Error> {
+        let wide = self.wide_a();
+        if mode == AddressMode::Accumulator {
+            let result = self.alter(op, self.acc(), wide);
+            self.acc_set(result);
+            return Ok(());
+        }
+        let target = self.fetch_target(mode, false)?;
+        let data = self.load(target, wide)?;
+        let result = self.alter(op, data, wide);
+        self.store(target, result, wide)
+    }
+
+    /// Branch on a condition, which takes a cycle more if taken and another one if that goes to
+    /// a new page in emulation mode
+    fn branch(&mut self, cond: bool) -> Result<(), Cpu816Error> {
+        let offset = self.fetch()?;
+        if cond {
+            let target = self.PC.wrapping_add_signed(i16::from(offset.cast_signed()));
+            self.extra += 1;
+            if self.E && target.to_be_bytes()[0] != self.PC.to_be_bytes()[0] {
+                self.extra += 1;
+            }
+            self.PC = target;
+        }
+        Ok(())
+    }
+
+    /// Copy a byte of a block move and step X and Y, running the instruction again until the
+    /// accumulator wraps around
+    fn block_move(&mut self, step: Word) -> Result<(), Cpu816Error> {
+        let destination = self.fetch()?;
+        let source = self.fetch()?;
+        self.DBR = destination;
+        let data = self.read(long(source, self.X))?;
+        self.write(long(destination, self.Y), data)?;
+        self.X = self.index(self.X.wrapping_add(step));
+        self.Y = self.index(self.Y.wrapping_add(step));
+        self.A = self.A.wrapping_sub(1);
+        if self.A != 0xFFFF {
+            self.PC = self.PC.wrapping_sub(3);
+            self.retiring = false;
+        }
+        Ok(())
+    }
+
+    /// Vector of the current mode
+    fn vector(&self, native: Word, emulation: Word) -> Word {
+        if self.E { emulation } else { native }
+    }
+
+    /// Push the return address and status and jump through an interrupt vector. Native mode
+    /// pushes the program bank too, and emulation mode clears the break flag for hardware
+    /// interrupts.
+    fn interrupt(&mut self, vector: Word, brk: bool) -> Result<(), Cpu816Error> {
+        if !self.E {
+            self.push(self.PBR)?;
+        }
+        self.push_word(self.PC)?;
+        let p = if self.E && !brk {
+            self.P & !X_FLAG
+        } else {
+            self.P
+        };
+        self.push(p)?;
+
+        self.flag_set(I_FLAG, true);
+        self.flag_set(D_FLAG, false);
+        self.PBR = 0;
+        self.PC = self.read_word(Target::bank0(vector))?;
+
+        // Native mode takes a cycle more to push the program bank
+        if !self.E {
+            self.extra += 1;
+        }
+        Ok(())
+    }
+
+    /// Go back to emulation mode with the registers the reset leaves behind, then jump through
+    /// the reset vector
+    fn reset_sequence(&mut self) -> Result<(), Cpu816Error> {
+        self.E = true;
+        self.D = 0;
+        self.DBR = 0;
+        self.PBR = 0;
+        self.S = 0x0100 | Word::from(self.S.to_le_bytes()[0].wrapping_sub(3));
+        self.p_set((self.P | I_FLAG) & !D_FLAG);
+        self.PC = self.read_word(Target::bank0(RESET))?;
+        Ok(())
+    }
+}
+
+impl<T> Cpu816<T>
+where
+    T: Device<Long>,
+{
+    /// Execute the next instruction, returning how many cycles it used
+    #[allow(clippy::too_many_lines)]
+    fn execute(&mut self) -> Result<u8, Cpu816Error> {
+        self.extra = 0;
+        self.op_pc = long(self.PBR, self.PC);
+        self.opcode = None;
+
+        if let Some(vector) = self.interrupts.pending {
+            match vector {
+                RESB => self.reset_sequence()?,
+                NMIB => self.interrupt(self.vector(NMI_NATIVE, NMI_EMULATION), false)?,
+                _ => self.interrupt(self.vector(IRQ_NATIVE, IRQ_EMULATION), false)?,
+            }
+            self.interrupts.pending = None;
+            self.interrupts.done();
+            self.retiring = false;
+            return Ok(7 + self.extra);
+        }
+
+        let opcode = self.fetch()?;
+        self.opcode = Some(opcode);
+        let Op(op, mode, cycles) = self.decoder[usize::from(opcode)];
+        let page = matches!(cycles, Cycles::Page(_));
+        let (wide_a, wide_x) = (self.wide_a(), self.wide_x());
+        self.retiring = true;
+
+        match op {
+            OpCode::Lda => {
+                let data = self.operand(mode, page, wide_a)?;
+                self.acc_set(data);
+                self.flags_zn(data, wide_a);
+            }
+            OpCode::Ldx => {
+                self.X = self.operand(mode, page, wide_x)?;
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Ldy => {
+                self.Y = self.operand(mode, page, wide_x)?;
+                self.flags_zn(self.Y, wide_x);
+            }
+            OpCode::Sta => {
+                let target = self.fetch_target(mode, false)?;
+                self.store(target, self.acc(), wide_a)?;
+            }
+            OpCode::Stx => {
+                let target = self.fetch_target(mode, false)?;
+                self.store(target, self.X, wide_x)?;
+            }
+            OpCode::Sty => {
+                let target = self.fetch_target(mode, false)?;
+                self.store(target, self.Y, wide_x)?;
+            }
+            OpCode::Stz => {
+                let target = self.fetch_target(mode, false)?;
+                self.store(target, 0, wide_a)?;
+            }
+            OpCode::And | OpCode::Ora | OpCode::Eor => {
+                let data = self.operand(mode, page, wide_a)?;
+                let result = match op {
+                    OpCode::And => self.acc() & data,
+                    OpCode::Ora => self.acc() | data,
+                    _ => self.acc() ^ data,
+                };
+                self.acc_set(result);
+                self.flags_zn(result, wide_a);
+            }
+            OpCode::Adc => {
+                let data = self.operand(mode, page, wide_a)?;
+                self.add(data);
+            }
+            OpCode::Sbc => {
+                let data = self.operand(mode, page, wide_a)?;
+                self.sub(data);
+            }
+            OpCode::Cmp => {
+                let data = self.operand(mode, page, wide_a)?;
+                self.compare(self.acc(), data, wide_a);
+            }
+            OpCode::Cpx => {
+                let data = self.operand(mode, page, wide_x)?;
+                self.compare(self.X, data, wide_x);
+            }
+            OpCode::Cpy => {
+                let data = self.operand(mode, page, wide_x)?;
+                self.compare(self.Y, data, wide_x);
+            }
+            OpCode::Bit => {
+                let data = self.operand(mode, page, wide_a)?;
+                self.flag_set(Z_FLAG, self.acc() & data == 0);
+                // Immediate only tests the bits
+                if mode != AddressMode::Immediate {
+                    let top = if wide_a { 0x8000 } else { 0x0080 };
+                    self.flag_set(N_FLAG, data & top > 0);
+                    self.flag_set(V_FLAG, data & (top >> 1) > 0);
+                }
+            }
+            OpCode::Asl
+            | OpCode::Lsr
+            | OpCode::Rol
+            | OpCode::Ror
+            | OpCode::Inc
+            | OpCode::Dec
+            | OpCode::Tsb
+            | OpCode::Trb => {
+                self.modify(op, mode)?;
+            }
+            OpCode::Inx => {
+                self.X = self.index(self.X.wrapping_add(1));
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Iny => {
+                self.Y = self.index(self.Y.wrapping_add(1));
+                self.flags_zn(self.Y, wide_x);
+            }
+            OpCode::Dex => {
+                self.X = self.index(self.X.wrapping_sub(1));
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Dey => {
+                self.Y = self.index(self.Y.wrapping_sub(1));
+                self.flags_zn(self.Y, wide_x);
+            }
+            OpCode::Tax => {
+                self.X = self.index(self.A);
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Tay => {
+                self.Y = self.index(self.A);
+                self.flags_zn(self.Y, wide_x);
+            }
+            OpCode::Txa => {
+                self.acc_set(self.X);
+                self.flags_zn(self.acc(), wide_a);
+            }
+            OpCode::Tya => {
+                self.acc_set(self.Y);
+                self.flags_zn(self.acc(), wide_a);
+            }
+            OpCode::Txy => {
+                self.Y = self.X;
+                self.flags_zn(self.Y, wide_x);
+            }
+            OpCode::Tyx => {
+                self.X = self.Y;
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Tsx => {
+                self.X = self.index(self.S);
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Txs => {
+                self.S = if self.E {
+                    0x0100 | (self.X & 0x00FF)
+                } else {
+                    self.X
+                };
+            }
+            OpCode::Tcs => {
+                self.S = if self.E {
+                    0x0100 | (self.A & 0x00FF)
+                } else {
+                    self.A
+                };
+            }
+            OpCode::Tsc => {
+                self.A = self.S;
+                self.flags_zn(self.A, true);
+            }
+            OpCode::Tcd => {
+                self.D = self.A;
+                self.flags_zn(self.D, true);
+            }
+            OpCode::Tdc => {
+                self.A = self.D;
+                self.flags_zn(self.A, true);
+            }
+            OpCode::Xba => {
+                self.A = self.A.swap_bytes();
+                self.flags_zn(self.A, false);
+            }
+            OpCode::Clc => self.flag_set(C_FLAG, false),
+            OpCode::Sec => self.flag_set(C_FLAG, true),
+            OpCode::Cli => self.flag_set(I_FLAG, false),
+            OpCode::Sei => self.flag_set(I_FLAG, true),
+            OpCode::Cld => self.flag_set(D_FLAG, false),
+            OpCode::Sed => self.flag_set(D_FLAG, true),
+            OpCode::Clv => self.flag_set(V_FLAG, false),
+            OpCode::Rep => {
+                let bits = self.fetch()?;
+                self.p_set(self.P & !bits);
+            }
+            OpCode::Sep => {
+                let bits = self.fetch()?;
+                self.p_set(self.P | bits);
+            }
+            OpCode::Xce => {
+                let carry = self.flag(C_FLAG);
+                self.flag_set(C_FLAG, self.E);
+                self.E = carry;
+                if self.E {
+                    self.S = 0x0100 | (self.S & 0x00FF);
+                }
+                self.p_set(self.P);
+            }
+            OpCode::Pha => self.push_register(self.acc(), wide_a)?,
+            OpCode::Phx => self.push_register(self.X, wide_x)?,
+            OpCode::Phy => self.push_register(self.Y, wide_x)?,
+            OpCode::Php => self.push(self.P)?,
+            OpCode::Phb => self.push(self.DBR)?,
+            OpCode::Phk => self.push(self.PBR)?,
+            OpCode::Phd => self.push_word_native(self.D)?,
+            OpCode::Pla => {
+                let data = self.pull_register(wide_a)?;
+                self.acc_set(data);
+                self.flags_zn(data, wide_a);
+            }
+            OpCode::Plx => {
+                self.X = self.pull_register(wide_x)?;
+                self.flags_zn(self.X, wide_x);
+            }
+            OpCode::Ply => {
+                self.Y = self.pull_register(wide_x)?;
+                self.flags_zn(self.Y, wide_x);
+            }
+            OpCode::Plp => {
+                let p = self.pull()?;
+                self.p_set(p);
+            }
+            OpCode::Plb => {
+                self.DBR = self.pull()?;
+                self.flags_zn(Word::from(self.DBR), false);
+            }
+            OpCode::Pld => {
+                self.D = self.pull_word_native()?;
+                self.flags_zn(self.D, true);
+            }
+            OpCode::Pea => {
+                let data = self.fetch_word()?;
+                self.push_word_native(data)?;
+            }
+            OpCode::Pei => {
+                let offset = self.fetch()?;
+                let pointer = self.direct(offset, 0);
+                let data = self.read_word(pointer)?;
+                self.push_word_native(data)?;
+            }
+            OpCode::Per => {
+                let offset = self.fetch_word()?;
+                self.push_word_native(self.PC.wrapping_add(offset))?;
+            }
+            OpCode::Bpl => self.branch(!self.flag(N_FLAG))?,
+            OpCode::Bmi => self.branch(self.flag(N_FLAG))?,
+            OpCode::Bvc => self.branch(!self.flag(V_FLAG))?,
+            OpCode::Bvs => self.branch(self.flag(V_FLAG))?,
+            OpCode::Bcc => self.branch(!self.flag(C_FLAG))?,
+            OpCode::Bcs => self.branch(self.flag(C_FLAG))?,
+            OpCode::Bne => self.branch(!self.flag(Z_FLAG))?,
+            OpCode::Beq => self.branch(self.flag(Z_FLAG))?,
+            OpCode::Bra => self.branch(true)?,
+            OpCode::Brl => {
+                let offset = self.fetch_word()?;
+                self.PC = self.PC.wrapping_add(offset);
+            }
+            OpCode::Jmp => {
+                let addr = self.fetch_word()?;
+                self.PC = match mode {
+                    AddressMode::Absolute => addr,
+                    AddressMode::AbsoluteIndirect => self.read_word(Target::bank0(addr))?,
+                    AddressMode::AbsoluteIndexedIndirect => {
+                        let pointer = long(self.PBR, addr.wrapping_add(self.X));
+                        self.read_word(Target::long(pointer))?
+                    }
+                    _ => return Err(self.invalid_address_mode()),
+                };
+            }
+            OpCode::Jml => {
+                let target = match mode {
+                    AddressMode::AbsoluteLong => self.fetch_long()?,
+                    AddressMode::AbsoluteIndirectLong => {
+                        let addr = self.fetch_word()?;
+                        self.pointer_long(Target::bank0(addr))?
+                    }
+                    _ => return Err(self.invalid_address_mode()),
+                };
+                (self.PBR, self.PC) = split(target);
+            }
+            OpCode::Jsr => {
+                let addr = self.fetch_word()?;
+                self.push_word(self.PC.wrapping_sub(1))?;
+                self.PC = match mode {
+                    AddressMode::Absolute => addr,
+                    AddressMode::AbsoluteIndexedIndirect => {
+                        let pointer = long(self.PBR, addr.wrapping_add(self.X));
+                        self.read_word(Target::long(pointer))?
+                    }
+                    _ => return Err(self.invalid_address_mode()),
+                };
+            }
+            OpCode::Jsl => {
+                let target = self.fetch_long()?;
+                self.push_native(self.PBR)?;
+                self.push_word_native(self.PC.wrapping_sub(1))?;
+                (self.PBR, self.PC) = split(target);
+            }
+            OpCode::Rts => {
+                self.PC = self.pull_word()?.wrapping_add(1);
+            }
+            OpCode::Rtl => {
+                self.PC = self.pull_word_native()?.wrapping_add(1);
+                self.PBR = self.pull_native()?;
+            }
+            OpCode::Rti => {
+                let p = self.pull()?;
+                self.p_set(p);
+                self.PC = self.pull_word()?;
+                if !self.E {
+                    self.PBR = self.pull()?;
+                    self.extra += 1;
+                }
+            }
+            OpCode::Brk => {
+                self.fetch()?;
+                self.interrupt(self.vector(BRK_NATIVE, IRQ_EMULATION), true)?;
+            }
+            OpCode::Cop => {
+                self.fetch()?;
+                self.interrupt(self.vector(COP_NATIVE, COP_EMULATION), true)?;
+            }
+            OpCode::Wdm => {
+                self.fetch()?;
+            }
+            OpCode::Nop => {}
+            OpCode::Wai => self.run = RunState::Waiting,
+            OpCode::Stp => self.run = RunState::Stopped,
+            OpCode::Mvn => self.block_move(1)?,
+            OpCode::Mvp => self.block_move(Word::MAX)?,
+        }
+        // Instructions the 65C816 added leave page 1 in emulation mode, but only until they are done
+        self.stack_wrap();
+
+        let base = match cycles {
+            Cycles::Constant(c) | Cycles::Page(c) | Cycles::Branch(c) => c,
+        };
+        Ok(base + self.extra)
+    }
+
+    /// Run the next instruction, or wait while the processor waits or is stopped
+    fn clock(&mut self) -> Result<(), Cpu816Error> {
+        self.interrupts.sample(true);
+
+        match self.run {
+            RunState::Running => {}
+            // WAI holds the processor until an interrupt arrives
+            RunState::Waiting if self.interrupts.wake(self.flag(I_FLAG)) => {
+                self.run = RunState::Running;
+            }
+            RunState::Waiting | RunState::Stopped | RunState::Jammed(_) => {
+                self.cycles += 1;
+                return Ok(());
+            }
+        }
+
+        let cycles = self.execute()?;
+        self.cycles += u64::from(cycles);
+        if self.retiring {
+            self.instructions += 1;
+        }
+        self.interrupts.poll_i = self.flag(I_FLAG);
+        self.interrupts.poll();
+        Ok(())
+    }
+}
+
+impl<T> Tickable for Cpu816<T>
+where
+    T: Device<Long>,
+{
+    fn tick(&mut self) -> Result<(), crate::Error> {
+        Ok(self.clock()?)
+    }
+
+    fn run_state_get(&self) -> RunState {
+        self.run
+    }
+}
+
+impl<T> Interruptible for Cpu816<T>
+where
+    T: Device<Long>,
+{
+    fn irq(&self) -> Line {
+        self.interrupts.irq.clone()
+    }
+
+    fn nmi(&self) -> Line {
+        self.interrupts.nmi.clone()
+    }
+}
+
+impl<T> Resettable for Cpu816<T>
+where
+    T: Device<Long>,
+{
+    /// Reset processor. The reset sequence runs on the next tick, leaving the processor in
+    /// emulation mode with PC at the reset vector.
+    fn reset(&mut self) -> Result<(), crate::Error> {
+        self.run = RunState::Running;
+        self.cycles = 0;
+        self.instructions = 0;
+        self.interrupts.reset();
+        self.interrupts.pending = Some(RESB);
+
+        Ok(())
+    }
+}
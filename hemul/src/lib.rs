@@ -1,37 +1,139 @@
-use std::{
-    error::Error,
-    ops::{Index, IndexMut},
-};
+use std::ops::{Index, IndexMut};
 
+pub mod bank;
 pub mod bus;
 pub mod cpu;
+pub mod cpu816;
+pub mod line;
 pub mod memory;
 pub mod oscillator;
+pub mod power;
+pub mod rom;
 
 pub type Word = u16;
 pub type Byte = u8;
+/// Address on the 24 bit bus of the 65C816, the top byte is unused
+pub type Long = u32;
+
+/// Errors that devices in the emulator fail with
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Cpu(#[from] cpu::CpuError),
+
+    #[error(transparent)]
+    Cpu816(#[from] cpu816::Cpu816Error),
+
+    #[error(transparent)]
+    Bus(#[from] bus::BusError),
+
+    #[error(transparent)]
+    Memory(#[from] memory::MemoryError),
+
+    #[error(transparent)]
+    Rom(#[from] rom::RomError),
+
+    #[error("failed to tick '{name}': {source}")]
+    Device { name: String, source: Box<Self> },
+}
 
 pub trait Addressable: Index<Word, Output = Byte> + IndexMut<Word, Output = Byte> {
     fn inside_bounds(&self, addr: Word) -> bool;
 }
 
+/// Something on the bus that the Cpu reads and writes.
+///
+/// Unlike [`Addressable`] a read can have side effects, like a peripheral that clears its status
+/// when its data register is read. Everything [`Addressable`] is a device without side effects.
+/// Devices on the 24 bit bus of the 65C816 take [`Long`] addresses.
+pub trait Device<A = Word> {
+    /// Whether something answers at the address, accessing anywhere else is a bus fault
+    fn inside_bounds(&self, addr: A) -> bool;
+
+    /// Read a byte without side effects, for snapshots and debuggers
+    fn peek(&self, addr: A) -> Byte;
+
+    /// Read a byte like the Cpu does
+    fn read(&mut self, addr: A) -> Byte {
+        self.peek(addr)
+    }
+
+    /// Whether a write to the address is allowed, writing anywhere else is a bus fault
+    fn writable(&self, _addr: A) -> bool {
+        true
+    }
+
+    /// Write a byte
+    fn write(&mut self, addr: A, data: Byte);
+}
+
+impl<T: Addressable> Device for T {
+    fn inside_bounds(&self, addr: Word) -> bool {
+        Addressable::inside_bounds(self, addr)
+    }
+
+    fn peek(&self, addr: Word) -> Byte {
+        self[addr]
+    }
+
+    fn write(&mut self, addr: Word, data: Byte) {
+        self[addr] = data;
+    }
+}
+
+/// Boxed [`Addressable`] devices, which the bus and the Cpu took before there were devices
+impl Device for Box<dyn Addressable> {
+    fn inside_bounds(&self, addr: Word) -> bool {
+        self.as_ref().inside_bounds(addr)
+    }
+
+    fn peek(&self, addr: Word) -> Byte {
+        self[addr]
+    }
+
+    fn write(&mut self, addr: Word, data: Byte) {
+        self[addr] = data;
+    }
+}
+
 pub trait Tickable {
-    fn tick(&mut self) -> Result<(), Box<dyn Error>>;
+    fn tick(&mut self) -> Result<(), Error>;
+
+    /// Whether the device runs, devices that can not halt always do
+    fn run_state_get(&self) -> RunState {
+        RunState::Running
+    }
+}
+
+/// Run state of a device that can halt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    /// Running normally
+    Running,
+    /// Waiting for an interrupt, like the 65C02 after WAI
+    Waiting,
+    /// Stopped until reset, like the 65C02 after STP
+    Stopped,
+    /// Jammed by an op code until reset
+    Jammed(Byte),
 }
 
 pub trait Resettable {
-    fn reset(&mut self) -> Result<(), Box<dyn Error>>;
+    fn reset(&mut self) -> Result<(), Error>;
 }
 
-pub type Interrupt = u8;
 pub trait Interruptible {
-    fn interrupt(&mut self, tp: impl Into<Interrupt>) -> Result<(), Box<dyn Error>>;
+    /// Handle to the level triggered IRQ line
+    fn irq(&self) -> line::Line;
+
+    /// Handle to the edge triggered NMI line
+    fn nmi(&self) -> line::Line;
 }
 
 pub trait Snapshottable {
     type Snapshot;
 
-    fn snapshot(&self) -> Result<Self::Snapshot, Box<dyn Error>>;
+    fn snapshot(&self) -> Result<Self::Snapshot, Error>;
 }
 
 #[macro_export]
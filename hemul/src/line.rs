@@ -0,0 +1,84 @@
+use std::{cell::Cell, rc::Rc};
+
+/// An open drain signal line, like the IRQ and NMI lines of the 6502.
+///
+/// Every handle to the line can pull it and it stays asserted as long as at least one of them
+/// does. Cloning a handle gives a new handle to the same line, that does not pull it.
+#[derive(Debug, Default)]
+pub struct Line {
+    /// Number of handles pulling the line
+    pulled: Rc<Cell<usize>>,
+
+    /// Whether this handle pulls the line
+    held: bool,
+}
+
+impl Line {
+    /// Pull the line, asserting it
+    pub fn assert(&mut self) {
+        if !self.held {
+            self.held = true;
+            self.pulled.set(self.pulled.get() + 1);
+        }
+    }
+
+    /// Let go of the line, it stays asserted while other handles pull it
+    pub fn release(&mut self) {
+        if self.held {
+            self.held = false;
+            self.pulled.set(self.pulled.get() - 1);
+        }
+    }
+
+    /// Whether any handle pulls the line
+    pub fn asserted(&self) -> bool {
+        self.pulled.get() > 0
+    }
+}
+
+impl Clone for Line {
+    fn clone(&self) -> Self {
+        Self {
+            pulled: Rc::clone(&self.pulled),
+            held: false,
+        }
+    }
+}
+
+impl Drop for Line {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wired_or() {
+        let line = Line::default();
+        let (mut a, mut b) = (line.clone(), line.clone());
+        assert!(!line.asserted());
+
+        a.assert();
+        a.assert();
+        b.assert();
+        a.release();
+        assert!(line.asserted());
+
+        b.release();
+        assert!(!line.asserted());
+    }
+
+    #[test]
+    fn test_drop_releases() {
+        let line = Line::default();
+        let mut a = line.clone();
+        a.assert();
+        assert!(line.asserted());
+
+        drop(a);
+        assert!(!line.asserted());
+    }
+}
@@ -1,12 +1,20 @@
 use std::{
-    error::Error,
     fs::File,
     io::prelude::*,
     ops::{Index, IndexMut},
     process::{Command, Stdio},
 };
 
-use crate::{Addressable, Byte, Snapshottable, Word};
+use thiserror::Error;
+
+use crate::{Addressable, Byte, Device, Long, Snapshottable, Word, power::PowerOn};
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Error, Debug)]
+pub enum MemoryError {
+    #[error("image of {0} bytes does not fit in {1} bytes of memory")]
+    TooLarge(usize, usize),
+}
 
 pub struct Memory(Vec<Byte>);
 
@@ -14,6 +22,59 @@ impl Memory {
     pub fn using(data: Vec<Byte>) -> Self {
         Self(data)
     }
+
+    /// Memory holding what RAM does when the power comes on
+    pub fn with_power_on(power_on: PowerOn) -> Self {
+        Self(power_on.bytes().take(Word::MAX as usize + 1).collect())
+    }
+
+    /// Copy data into memory, starting at address 0. Fails if the data does not fit.
+    pub fn load(&mut self, data: &[Byte]) -> Result<(), MemoryError> {
+        if data.len() > self.0.len() {
+            return Err(MemoryError::TooLarge(data.len(), self.0.len()));
+        }
+        self.0[..data.len()].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// Assemble a program with vasm, returning the machine code starting at address 0
+#[allow(clippy::missing_panics_doc)]
+pub fn assemble(program: &str) -> Vec<Byte> {
+    // Get vasm6502_oldstyle path with fallback logic
+    let bin =
+        std::env::var("VASM6502_OLDSTYLE").unwrap_or_else(|_| "vasm6502_oldstyle".to_string());
+
+    // let child = Command::new("xa")
+    //     .args(["-o", "-", "/dev/stdin"])
+    #[allow(clippy::zombie_processes)]
+    let child = Command::new(&bin)
+        .args([
+            "-Fbin",
+            "-dotdir",
+            "-o",
+            "/dev/stdout",
+            "-quiet",
+            "/dev/stdin",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start assembler");
+
+    child
+        .stdin
+        .expect("failed to get stdin")
+        .write_all(program.as_bytes())
+        .expect("failed to write to stdin");
+
+    let mut data = Vec::new();
+    let _ = child
+        .stdout
+        .expect("failed to get stdout")
+        .read_to_end(&mut data)
+        .expect("failed to read stdout");
+    data
 }
 
 impl Default for Memory {
@@ -42,10 +103,34 @@ impl IndexMut<Word> for Memory {
     }
 }
 
+/// Memory on the 24 bit bus of the 65C816, as large as the data it is made from
+impl Device<Long> for Memory {
+    fn inside_bounds(&self, addr: Long) -> bool {
+        usize::try_from(addr).is_ok_and(|addr| addr < self.0.len())
+    }
+
+    fn peek(&self, addr: Long) -> Byte {
+        usize::try_from(addr)
+            .ok()
+            .and_then(|addr| self.0.get(addr))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn write(&mut self, addr: Long, data: Byte) {
+        if let Some(byte) = usize::try_from(addr)
+            .ok()
+            .and_then(|addr| self.0.get_mut(addr))
+        {
+            *byte = data;
+        }
+    }
+}
+
 impl Snapshottable for Memory {
     type Snapshot = Vec<Byte>;
 
-    fn snapshot(&self) -> Result<Self::Snapshot, Box<dyn Error>> {
+    fn snapshot(&self) -> Result<Self::Snapshot, crate::Error> {
         Ok(self.0.clone())
     }
 }
@@ -74,53 +159,37 @@ impl From<String> for Memory {
     }
 }
 
-#[allow(clippy::fallible_impl_from)]
 impl From<&str> for Memory {
     fn from(value: &str) -> Self {
-        // Get vasm6502_oldstyle path with fallback logic
-        let bin =
-            std::env::var("VASM6502_OLDSTYLE").unwrap_or_else(|_| "vasm6502_oldstyle".to_string());
-
-        // let child = Command::new("xa")
-        //     .args(["-o", "-", "/dev/stdin"])
-        #[allow(clippy::zombie_processes)]
-        let child = Command::new(&bin)
-            .args([
-                "-Fbin",
-                "-dotdir",
-                "-o",
-                "/dev/stdout",
-                "-quiet",
-                "/dev/stdin",
-            ])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("failed to start assembler");
-
-        child
-            .stdin
-            .expect("failed to get stdin")
-            .write_all(value.as_bytes())
-            .expect("failed to write to stdin");
-
-        let mut data = Vec::new();
-        let _ = child
-            .stdout
-            .expect("failed to get stdout")
-            .read_to_end(&mut data)
-            .expect("failed to read stdout");
+        let mut data = assemble(value);
         data.resize(Word::MAX as usize + 1, 0);
         Self::using(data)
     }
 }
 
 impl From<&[u8]> for Memory {
+    /// # Panics
+    ///
+    /// If the data is larger than the 64K address space
     fn from(value: &[u8]) -> Self {
         let mut memory = Self::default();
-        for (i, b) in value.iter().enumerate() {
-            memory.0[i] = *b;
-        }
+        memory.load(value).expect("failed to load data into memory");
         memory
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let mut memory = Memory::using(vec![0; 4]);
+        memory.load(&[1, 2, 3, 4]).expect("Loading failed");
+        assert_eq!(memory[3], 4);
+        assert!(matches!(
+            memory.load(&[0; 5]),
+            Err(MemoryError::TooLarge(5, 4))
+        ));
+    }
+}
@@ -1,9 +1,6 @@
-use std::{
-    error::Error,
-    time::{Duration, Instant},
-};
+use std::time::{Duration, Instant};
 
-use crate::Tickable;
+use crate::{Error, RunState, Tickable};
 
 pub struct Oscillator {
     last_pass: Instant,
@@ -35,17 +32,35 @@ impl Oscillator {
 }
 
 impl Tickable for Oscillator {
-    fn tick(&mut self) -> Result<(), Box<dyn Error>> {
+    fn tick(&mut self) -> Result<(), Error> {
         let now = Instant::now();
         let delta = now - self.last_pass;
         if delta > self.delta {
             for (name, device) in &mut self.devices {
-                device
-                    .tick()
-                    .map_err(|e| format!("failed to tick '{name}': {e}"))?;
+                device.tick().map_err(|e| {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(device = %name, error = %e, "device failed");
+                    Error::Device {
+                        name: name.clone(),
+                        source: Box::new(e),
+                    }
+                })?;
             }
             self.last_pass = now;
         }
         Ok(())
     }
+
+    /// Halted if a device is stopped or jammed, waiting if one waits and running otherwise
+    fn run_state_get(&self) -> RunState {
+        self.devices
+            .iter()
+            .map(|(_, device)| device.run_state_get())
+            .fold(RunState::Running, |acc, state| match (acc, state) {
+                (RunState::Stopped | RunState::Jammed(_), _) => acc,
+                (_, RunState::Stopped | RunState::Jammed(_))
+                | (RunState::Running, RunState::Waiting) => state,
+                _ => acc,
+            })
+    }
 }
@@ -0,0 +1,90 @@
+use crate::Byte;
+
+/// What registers and RAM hold when the power comes on, which is undefined on the real chips.
+/// Anything but [`PowerOn::Zeroed`] helps finding firmware that assumes a clean machine.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PowerOn {
+    /// Every byte is zero
+    #[default]
+    Zeroed,
+    /// Every byte is the same
+    Pattern(Byte),
+    /// Bytes are random, but the same for the same seed
+    Random(u64),
+}
+
+impl PowerOn {
+    /// Endless stream of bytes to fill registers or RAM with
+    pub fn bytes(self) -> impl Iterator<Item = Byte> {
+        let mut state = match self {
+            Self::Random(seed) => seed,
+            _ => 0,
+        };
+        std::iter::from_fn(move || {
+            Some(match self {
+                Self::Zeroed => 0,
+                Self::Pattern(byte) => byte,
+                Self::Random(_) => {
+                    // SplitMix64, see <https://prng.di.unimi.it/splitmix64.c>
+                    state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+                    let mut z = state;
+                    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                    (z ^ (z >> 31)).to_le_bytes()[0]
+                }
+            })
+        })
+    }
+}
+
+impl std::str::FromStr for PowerOn {
+    type Err = String;
+
+    /// Parse `zeroed`, `pattern:<byte>` or `random:<seed>`, with the byte in hex
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "zeroed" => Ok(Self::Zeroed),
+            Some(("pattern", byte)) => Byte::from_str_radix(byte.trim_start_matches("0x"), 16)
+                .map(Self::Pattern)
+                .map_err(|e| format!("invalid pattern '{byte}': {e}")),
+            Some(("random", seed)) => seed
+                .parse()
+                .map(Self::Random)
+                .map_err(|e| format!("invalid seed '{seed}': {e}")),
+            _ => Err(format!(
+                "invalid power-on state '{s}', expected zeroed, pattern:<byte> or random:<seed>"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        assert!(PowerOn::Zeroed.bytes().take(16).all(|b| b == 0));
+        assert!(PowerOn::Pattern(0xA5).bytes().take(16).all(|b| b == 0xA5));
+
+        let random: Vec<Byte> = PowerOn::Random(42).bytes().take(16).collect();
+        assert_eq!(
+            random,
+            PowerOn::Random(42).bytes().take(16).collect::<Vec<_>>()
+        );
+        assert_ne!(
+            random,
+            PowerOn::Random(43).bytes().take(16).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("zeroed".parse(), Ok(PowerOn::Zeroed));
+        assert_eq!("pattern:0xA5".parse(), Ok(PowerOn::Pattern(0xA5)));
+        assert_eq!("pattern:ff".parse(), Ok(PowerOn::Pattern(0xFF)));
+        assert_eq!("random:42".parse(), Ok(PowerOn::Random(42)));
+        assert!("random".parse::<PowerOn>().is_err());
+        assert!("pattern:100".parse::<PowerOn>().is_err());
+    }
+}
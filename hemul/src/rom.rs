@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::{Byte, Device, Snapshottable, Word};
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Error, Debug)]
+pub enum RomError {
+    #[error("image of {0} bytes does not fit in {1} bytes of ROM")]
+    TooLarge(usize, usize),
+
+    #[error("failed to read ROM image: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// What a ROM does with writes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WritePolicy {
+    /// Drop them, like real hardware does
+    #[default]
+    Ignore,
+    /// Drop them and log a warning with the `hemul::rom` target, with the `tracing` feature
+    Log,
+    /// Refuse them, so the Cpu fails with a bus fault
+    Error,
+}
+
+/// Read only memory, as large as the image it is made from. The Cpu reads it like [`Memory`] but
+/// writes do not change it.
+///
+/// [`Memory`]: crate::memory::Memory
+pub struct Rom {
+    data: Vec<Byte>,
+    policy: WritePolicy,
+}
+
+impl Rom {
+    pub fn using(data: Vec<Byte>) -> Self {
+        Self {
+            data,
+            policy: WritePolicy::default(),
+        }
+    }
+
+    /// ROM of the size with the image at the start and the rest filled with the value. Fails if
+    /// the image does not fit.
+    pub fn padded(image: &[Byte], size: usize, fill: Byte) -> Result<Self, RomError> {
+        if image.len() > size {
+            return Err(RomError::TooLarge(image.len(), size));
+        }
+        let mut data = vec![fill; size];
+        data[..image.len()].copy_from_slice(image);
+        Ok(Self::using(data))
+    }
+
+    /// ROM holding the image in a file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RomError> {
+        Ok(Self::using(std::fs::read(path)?))
+    }
+
+    /// ROM of the size holding the image in a file, see [`Rom::padded`]
+    pub fn open_padded(path: impl AsRef<Path>, size: usize, fill: Byte) -> Result<Self, RomError> {
+        Self::padded(&std::fs::read(path)?, size, fill)
+    }
+
+    /// Set what writes do, they are ignored by default
+    pub fn write_policy_set(&mut self, policy: WritePolicy) {
+        self.policy = policy;
+    }
+}
+
+impl Device for Rom {
+    fn inside_bounds(&self, addr: Word) -> bool {
+        self.data.len() > addr.into()
+    }
+
+    fn writable(&self, _addr: Word) -> bool {
+        self.policy != WritePolicy::Error
+    }
+
+    fn peek(&self, addr: Word) -> Byte {
+        self.data
+            .get(usize::from(addr))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn write(&mut self, addr: Word, data: Byte) {
+        if self.policy == WritePolicy::Log {
+            log_write(addr, data);
+        }
+    }
+}
+
+/// Warn about a write to ROM
+#[cfg(feature = "tracing")]
+fn log_write(addr: Word, data: Byte) {
+    tracing::warn!(target: "hemul::rom", addr, data, "write to ROM");
+}
+
+/// Writes to ROM are only logged with the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+fn log_write(_addr: Word, _data: Byte) {}
+
+impl Snapshottable for Rom {
+    type Snapshot = Vec<Byte>;
+
+    fn snapshot(&self) -> Result<Self::Snapshot, crate::Error> {
+        Ok(self.data.clone())
+    }
+}
@@ -0,0 +1,279 @@
+// Testing of the 65C816, with programs in machine code since the assembler only knows the 65C02
+
+use hemul::{
+    Byte, Interruptible, Resettable, Tickable,
+    bus::Bus,
+    cpu816::{Cpu816, Cpu816Error},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+/// Two banks of memory with the program at $00:0200, where the reset vector points
+fn memory(program: &[Byte]) -> Vec<Byte> {
+    let mut data = vec![0; 0x2_0000];
+    data[0x0200..0x0200 + program.len()].copy_from_slice(program);
+    data[0xFFFC..=0xFFFD].copy_from_slice(&[0x00, 0x02]);
+    data
+}
+
+fn cpu(data: Vec<Byte>) -> Cpu816<Memory> {
+    let mut cpu = Cpu816::new(Memory::using(data));
+    cpu.reset().expect("Resetting CPU failed");
+    cpu
+}
+
+#[test]
+fn test_65c816_reset() {
+    let mut cpu = cpu(memory(&[0xEA]));
+    cpu.tick_until_nop().expect("Running program failed");
+    assert!(cpu.emulation_get());
+    assert_eq!(cpu.pc_get(), 0x0200);
+    assert_eq!(cpu.pbr_get(), 0x00);
+    assert_eq!(cpu.dbr_get(), 0x00);
+    assert_eq!(cpu.d_get(), 0x0000);
+    assert_eq!(cpu.sp_get(), 0x01FD);
+    assert_eq!(cpu.p_get() & 0b0011_0100, 0b0011_0100);
+}
+
+#[test]
+fn test_65c816_emulation_keeps_b() {
+    let mut cpu = cpu(memory(&[
+        0xA9, 0x12, // LDA #$12
+        0xEB, //       XBA
+        0xA9, 0x34, // LDA #$34
+        0xEA, //       NOP
+    ]));
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.a_get(), 0x1234);
+}
+
+#[test]
+fn test_65c816_native_16_bit() {
+    let mut cpu = cpu(memory(&[
+        0x18, //             CLC
+        0xFB, //             XCE
+        0xC2, 0x30, //       REP #$30
+        0xA9, 0x34, 0x12, // LDA #$1234
+        0x18, //             CLC
+        0x69, 0x00, 0x0F, // ADC #$0F00
+        0xA2, 0xEF, 0xBE, // LDX #$BEEF
+        0xE2, 0x10, //       SEP #$10
+        0xA0, 0xCD, //       LDY #$CD
+        0xEA, //             NOP
+    ]));
+    cpu.tick_until_nop().expect("Running program failed");
+    assert!(!cpu.emulation_get());
+    assert_eq!(cpu.a_get(), 0x2134);
+    // 8 bit index registers lose their high byte
+    assert_eq!(cpu.x_get(), 0x00EF);
+    assert_eq!(cpu.y_get(), 0x00CD);
+    assert_eq!(cpu.instructions_get(), 9);
+}
+
+#[test]
+fn test_65c816_long_addressing() {
+    let mut cpu = cpu(memory(&[
+        0xA9, 0x5A, //             LDA #$5A
+        0x8F, 0x45, 0x23, 0x01, // STA $01:2345
+        0xA9, 0x00, //             LDA #$00
+        0xAF, 0x45, 0x23, 0x01, // LDA $01:2345
+        0xA9, 0x01, //             LDA #$01
+        0x48, //                   PHA
+        0xAB, //                   PLB
+        0xAE, 0x45, 0x23, //       LDX $2345
+        0xEA, //                   NOP
+    ]));
+    cpu.tick_for(5).expect("Running program failed");
+    assert_eq!(cpu.a_get(), 0x005A);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.dbr_get(), 0x01);
+    assert_eq!(cpu.x_get(), 0x005A);
+}
+
+#[test]
+fn test_65c816_direct_page() {
+    let mut cpu = cpu(memory(&[
+        0x18, //             CLC
+        0xFB, //             XCE
+        0xC2, 0x20, //       REP #$20
+        0xA9, 0x00, 0x04, // LDA #$0400
+        0x5B, //             TCD
+        0xE2, 0x20, //       SEP #$20
+        0xA9, 0x99, //       LDA #$99
+        0x85, 0x10, //       STA $10
+        0xAE, 0x10, 0x04, // LDX $0410
+        0xEA, //             NOP
+    ]));
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.d_get(), 0x0400);
+    assert_eq!(cpu.x_get(), 0x0099);
+}
+
+#[test]
+fn test_65c816_block_move() {
+    let mut data = memory(&[
+        0x18, //             CLC
+        0xFB, //             XCE
+        0xC2, 0x30, //       REP #$30
+        0xA9, 0x03, 0x00, // LDA #$0003
+        0xA2, 0x00, 0x00, // LDX #$0000
+        0xA0, 0x00, 0x10, // LDY #$1000
+        0x54, 0x00, 0x01, // MVN $01,$00
+        0xEA, //             NOP
+    ]);
+    data[0x1_0000..0x1_0005].copy_from_slice(&[0x11, 0x22, 0x33, 0x44, 0x55]);
+    let mut cpu = cpu(data);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.a_get(), 0xFFFF);
+    assert_eq!(cpu.x_get(), 0x0004);
+    assert_eq!(cpu.y_get(), 0x1004);
+    assert_eq!(cpu.dbr_get(), 0x00);
+    // The move counts as one instruction however many bytes it copies
+    assert_eq!(cpu.instructions_get(), 7);
+}
+
+#[test]
+fn test_65c816_jsl_rtl() {
+    let mut data = memory(&[
+        0x22, 0x00, 0x80, 0x01, // JSL $01:8000
+        0xEA, //                   NOP
+    ]);
+    data[0x1_8000..0x1_8003].copy_from_slice(&[
+        0xA9, 0x77, // LDA #$77
+        0x6B, //       RTL
+    ]);
+    let mut cpu = cpu(data);
+    cpu.tick_for(3).expect("Running program failed");
+    assert_eq!(cpu.pbr_get(), 0x01);
+    assert_eq!(cpu.pc_get(), 0x8002);
+    assert_eq!(cpu.a_get(), 0x0077);
+    cpu.tick().expect("Running program failed");
+    assert_eq!(cpu.pbr_get(), 0x00);
+    assert_eq!(cpu.pc_get(), 0x0204);
+    assert_eq!(cpu.sp_get(), 0x01FD);
+}
+
+#[test]
+fn test_65c816_emulation_stack_leaves_page_1() {
+    let mut cpu = cpu(memory(&[
+        0xA9, 0x34, //       LDA #$34
+        0xEB, //             XBA
+        0xA9, 0x12, //       LDA #$12
+        0x5B, //             TCD
+        0xA2, 0x00, //       LDX #$00
+        0x9A, //             TXS
+        0x0B, //             PHD
+        0xAD, 0xFF, 0x00, // LDA $00FF
+        0xAC, 0x00, 0x01, // LDY $0100
+        0xEA, //             NOP
+    ]));
+    cpu.tick_until_nop().expect("Running program failed");
+    assert!(cpu.emulation_get());
+    // PHD pushes below page 1, and the stack pointer is back on it afterwards
+    assert_eq!(cpu.a_get() & 0x00FF, 0x0012);
+    assert_eq!(cpu.y_get(), 0x0034);
+    assert_eq!(cpu.sp_get(), 0x01FE);
+}
+
+#[test]
+fn test_65c816_emulation_stack_wraps() {
+    let mut cpu = cpu(memory(&[
+        0xA2, 0x00, //       LDX #$00
+        0x9A, //             TXS
+        0xA9, 0x12, //       LDA #$12
+        0x48, //             PHA
+        0xA9, 0x34, //       LDA #$34
+        0x48, //             PHA
+        0xAC, 0xFF, 0x01, // LDY $01FF
+        0xEA, //             NOP
+    ]));
+    cpu.tick_until_nop().expect("Running program failed");
+    // PHA stays on page 1
+    assert_eq!(cpu.y_get(), 0x0034);
+    assert_eq!(cpu.sp_get(), 0x01FE);
+}
+
+#[test]
+fn test_65c816_native_brk() {
+    let mut data = memory(&[
+        0x18, //       CLC
+        0xFB, //       XCE
+        0x00, 0x00, // BRK
+    ]);
+    // BRK in native mode has its own vector, and pushes the program bank
+    data[0xFFE6..=0xFFE7].copy_from_slice(&[0x00, 0x03]);
+    data[0x0300] = 0xEA;
+    let mut cpu = cpu(data);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.pc_get(), 0x0300);
+    assert_eq!(cpu.sp_get(), 0x01F9);
+}
+
+#[test]
+fn test_65c816_native_nmi() {
+    let mut data = memory(&[
+        0x18, //       CLC
+        0xFB, //       XCE
+        0x80, 0xFE, // BRA *
+    ]);
+    data[0xFFEA..=0xFFEB].copy_from_slice(&[0x00, 0x03]);
+    data[0x0300] = 0xEA;
+    let mut cpu = cpu(data);
+    cpu.tick_for(4).expect("Running program failed");
+    let mut nmi = cpu.nmi();
+    nmi.assert();
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.pc_get(), 0x0300);
+    assert_eq!(cpu.sp_get(), 0x01F9);
+}
+
+#[test]
+fn test_65c816_bus_fault() {
+    let mut cpu = cpu(memory(&[
+        0xAF, 0x00, 0x00, 0x05, // LDA $05:0000
+        0xEA, //                   NOP
+    ]));
+    let err = cpu.tick_until_nop().expect_err("LDA should fail");
+    assert_eq!(
+        err,
+        Cpu816Error::OutOfBounds {
+            pc: 0x0000_0200,
+            opcode: Some(0xAF),
+            addr: 0x0005_0000,
+        }
+    );
+}
+
+#[test]
+fn test_65c816_bus() {
+    let mut bus = Bus::long();
+    bus.connect(
+        "ram",
+        0x00_0000,
+        0x01_FFFF,
+        Box::new(Memory::using(memory(&[
+            0xAF, 0x34, 0x12, 0x05, // LDA $05:1234
+            0x8F, 0x00, 0x80, 0x01, // STA $01:8000
+            0xAE, 0x00, 0x80, //       LDX $8000
+            0xEA, //                   NOP
+        ]))),
+    )
+    .expect("Connecting device failed");
+    let mut data = vec![0; 0x1_0000];
+    data[0x1234] = 0x5A;
+    bus.connect(
+        "bank 5",
+        0x05_0000,
+        0x05_FFFF,
+        Box::new(Memory::using(data)),
+    )
+    .expect("Connecting device failed");
+
+    let mut cpu = Cpu816::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.a_get(), 0x005A);
+    // Bank 0 and bank 1 are different memory
+    assert_eq!(cpu.x_get(), 0x0000);
+}
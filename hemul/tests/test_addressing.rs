@@ -0,0 +1,210 @@
+// Testing of address modes where they wrap around the end of a page or of memory
+
+extern crate hemul;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[test]
+fn test_addressing_immediate() {
+    // The operand is fetched from $FFFF and the PC wraps around to $0000
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    NOP
+    .org    $FFFC
+    .word   $FFFE
+    LDA     #$42
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+    assert_eq!(snapshot.PC, 0x0000);
+}
+
+#[test]
+fn test_addressing_zero_page() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     $FF
+    NOP
+    .org    $00FF
+    .byte   $42
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_addressing_zero_page_x() {
+    // Indexing wraps around inside the zero page
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDX     #$10
+    LDA     $F8,X
+    NOP
+    .org    $0008
+    .byte   $42
+    .org    $0108
+    .byte   $01
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_addressing_zero_page_y() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDY     #$10
+    LDX     $F8,Y
+    NOP
+    .org    $0008
+    .byte   $42
+    .org    $0108
+    .byte   $01
+        "#
+    );
+    assert_eq!(snapshot.X, 0x42);
+}
+
+#[test]
+fn test_addressing_absolute() {
+    // The op code is at $FFFF and the operand wraps around to $0000
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    .word   $2000
+    NOP
+    .org    $2000
+    .byte   $42
+    .org    $FFFC
+    .word   $FFFF
+    .byte   $00
+    .byte   $AD         ; LDA $2000
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+    assert_eq!(snapshot.PC, 0x0002);
+}
+
+#[test]
+fn test_addressing_absolute_x() {
+    // Indexing past $FFFF wraps around to the zero page
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDX     #$10
+    LDA     $FFF8,X
+    NOP
+    .org    $0008
+    .byte   $42
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_addressing_absolute_y() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDY     #$10
+    LDA     $FFF8,Y
+    NOP
+    .org    $0008
+    .byte   $42
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_addressing_indirect() {
+    // The NMOS 6502 reads the high byte of the target from the start of the same page
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    JMP     ($30FF)
+    .org    $2000
+    LDY     #$01
+    NOP
+    .org    $3000
+    .byte   $20
+    .org    $30FF
+    .word   $4000
+    .org    $4000
+    LDY     #$02
+    NOP
+        "#
+    );
+    assert_eq!(snapshot.Y, 0x01);
+}
+
+#[test]
+fn test_addressing_indexed_indirect() {
+    // The pointer at $FF takes its high byte from $00, which holds the first op code
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDX     #$10
+    LDA     ($EF,X)
+    NOP
+    .org    $00FF
+    .byte   $34
+    .byte   $20
+    .org    $2034
+    .byte   $01
+    .org    $A234
+    .byte   $42
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_addressing_indirect_indexed() {
+    // The pointer at $FF takes its high byte from $00, and indexing past $FFFF wraps around
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDY     #$10
+    LDA     ($FF),Y
+    TAX
+    LDA     ($80),Y
+    NOP
+    .org    $0008
+    .byte   $43
+    .org    $0080
+    .word   $FFF8
+    .org    $00FF
+    .byte   $F8
+    .byte   $20
+    .org    $2108
+    .byte   $01
+    .org    $A108
+    .byte   $42
+        "#
+    );
+    assert_eq!(snapshot.X, 0x42);
+    assert_eq!(snapshot.A, 0x43);
+}
+
+#[test]
+fn test_addressing_relative() {
+    // Branching back from the start of memory wraps around to the end
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDX     #$01
+    .byte   $D0, $F0    ; BNE -16
+    .org    $FFF4
+    LDY     #$42
+    NOP
+        "#
+    );
+    assert_eq!(snapshot.Y, 0x42);
+    assert_eq!(snapshot.PC, 0xFFF6);
+}
@@ -0,0 +1,115 @@
+// Testing of bank switching on the bus
+
+use hemul::{
+    Device, Resettable, Snapshottable,
+    bank::{Banked, Latch, Select},
+    bus::Bus,
+    cpu::Cpu,
+    memory::Memory,
+};
+
+extern crate hemul;
+
+/// Cpu with RAM at $0000-$7FFF, four banks of 16K at $8000-$BFFF selected through a latch at
+/// $C000 and the program at $FF00-$FFFF
+fn cpu(program: &str) -> (Cpu<Bus>, Select) {
+    let banked = Banked::new(
+        (0..4)
+            .map(|_| Box::new(Memory::using(vec![0; 0x4000])) as _)
+            .collect(),
+    );
+    let select = banked.select();
+
+    let mut bus = Bus::default();
+    bus.connect("ram", 0x0000, 0x7FFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    bus.connect("banks", 0x8000, 0xBFFF, Box::new(banked))
+        .expect("Connecting device failed");
+    bus.connect(
+        "latch",
+        0xC000,
+        0xC000,
+        Box::new(Latch::new(select.clone(), 0x03)),
+    )
+    .expect("Connecting device failed");
+    bus.connect("rom", 0xFF00, 0xFFFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    (cpu, select)
+}
+
+#[test]
+fn test_banks_switch() {
+    let (mut cpu, select) = cpu(r#"
+        ;;
+    LDA     #$01
+    STA     $C000
+    LDA     #$11
+    STA     $8000
+    LDA     #$02
+    STA     $C000
+    LDA     #$22
+    STA     $8000
+    LDA     #$01
+    STA     $C000
+    LDX     $8000
+    LDY     $C000
+    NOP
+        "#);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.x_get(), 0x11);
+    assert_eq!(cpu.y_get(), 0x01);
+    assert_eq!(select.get(), 1);
+
+    // Snapshots see the bank that is mapped in
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.dump[0x8000], 0x11);
+    select.set(2);
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.dump[0x8000], 0x22);
+    select.set(0);
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.dump[0x8000], 0x00);
+}
+
+#[test]
+fn test_banks_latch_mask() {
+    let (mut cpu, select) = cpu(r#"
+        ;;
+    LDA     #$FE
+    STA     $C000
+    NOP
+        "#);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(select.get(), 2);
+}
+
+#[test]
+fn test_banks_missing_bank() {
+    let (mut cpu, select) = cpu(r#"
+        ;;
+    LDA     $8000
+    NOP
+        "#);
+    select.set(7);
+    cpu.tick_until_nop()
+        .expect_err("Reading a bank that does not exist should fail");
+}
+
+#[test]
+fn test_banks_snapshot() {
+    let mut banked = Banked::new(vec![
+        Box::new(Memory::using(vec![0; 4])),
+        Box::new(Memory::using(vec![0; 2])),
+    ]);
+    let select = banked.select();
+    banked.write(0x0000, 0x11);
+    select.set(1);
+    banked.write(0x0001, 0x22);
+
+    // Every bank is in the snapshot, not only the one that is mapped in
+    let snapshot = banked.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.selected, 1);
+    assert_eq!(snapshot.banks, vec![vec![0x11, 0, 0, 0], vec![0, 0x22]]);
+}
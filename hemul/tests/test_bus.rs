@@ -0,0 +1,168 @@
+// Testing of how the bus maps addresses to devices
+
+use hemul::{
+    Device, Resettable, Snapshottable,
+    bus::{Bus, BusError, Unmapped},
+    cpu::{Cpu, CpuError},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+#[test]
+fn test_bus_relative_addressing() {
+    let mut bus = Bus::default();
+    bus.connect("ram", 0x6000, 0x60FF, Box::new(Memory::using(vec![0; 256])))
+        .expect("Connecting device failed");
+
+    bus.write(0x6000, 0x11);
+    bus.write(0x60FF, 0x22);
+    assert_eq!(bus.read(0x6000), 0x11);
+    assert_eq!(bus.read(0x60FF), 0x22);
+    assert!(bus.inside_bounds(0x60FF));
+    assert!(!bus.inside_bounds(0x6100));
+
+    let dump = bus.snapshot().expect("Failed to create snapshot");
+    assert_eq!(dump.len(), 0x6100);
+    assert_eq!(dump[0x6000], 0x11);
+    assert_eq!(dump[0x60FF], 0x22);
+}
+
+#[test]
+fn test_bus_relative_addressing_smaller_device() {
+    let mut bus = Bus::default();
+    bus.connect("ram", 0x6000, 0x6FFF, Box::new(Memory::using(vec![0; 256])))
+        .expect("Connecting device failed");
+
+    // Past the end of the device is out of bounds, even inside the window
+    assert!(bus.inside_bounds(0x60FF));
+    assert!(!bus.inside_bounds(0x6100));
+}
+
+#[test]
+fn test_bus_mirrored() {
+    let mut bus = Bus::default();
+    bus.connect_mirrored(
+        "ram",
+        0x6000,
+        0x6FFF,
+        0x00FF,
+        Box::new(Memory::using(vec![0; 256])),
+    )
+    .expect("Connecting device failed");
+
+    bus.write(0x6042, 0x37);
+    assert_eq!(bus.read(0x6142), 0x37);
+    assert_eq!(bus.read(0x6F42), 0x37);
+    bus.write(0x6FFF, 0x99);
+    assert_eq!(bus.read(0x60FF), 0x99);
+    assert!(bus.inside_bounds(0x6FFF));
+
+    let dump = bus.snapshot().expect("Failed to create snapshot");
+    assert_eq!(dump[0x6542], 0x37);
+    assert_eq!(dump[0x6AFF], 0x99);
+}
+
+#[test]
+fn test_bus_overlap() {
+    let mut bus = Bus::default();
+    bus.connect("ram", 0x0000, 0x7FFF, Box::new(Memory::default()))
+        .expect("Connecting device failed");
+    let err = bus
+        .connect("via", 0x7FF0, 0x800F, Box::new(Memory::using(vec![0; 32])))
+        .expect_err("Overlapping windows should be rejected");
+    assert_eq!(
+        err,
+        BusError::Overlap {
+            name: "via".into(),
+            other: "ram".into(),
+            start: 0x7FF0,
+            end: 0x7FFF,
+        }
+    );
+
+    // The rejected device is not connected
+    assert!(!bus.inside_bounds(0x8000));
+}
+
+#[test]
+fn test_bus_shared_page() {
+    let mut bus = Bus::default();
+    bus.connect("via", 0x8000, 0x800F, Box::new(Memory::using(vec![1; 16])))
+        .expect("Connecting device failed");
+    bus.connect("acia", 0x8010, 0x8013, Box::new(Memory::using(vec![2; 4])))
+        .expect("Connecting device failed");
+    bus.connect("rom", 0x8100, 0xFFFF, Box::new(Memory::default()))
+        .expect("Connecting device failed");
+
+    assert_eq!(bus.read(0x800F), 1);
+    assert_eq!(bus.read(0x8010), 2);
+    assert_eq!(bus.read(0x8013), 2);
+    assert!(!bus.inside_bounds(0x8014));
+    assert!(!bus.inside_bounds(0x80FF));
+    assert!(bus.inside_bounds(0x8100));
+}
+
+/// Cpu with the program at $0000-$01FF and $FF00-$FFFF, and nothing in between
+fn cpu(program: &str, unmapped: Unmapped) -> Cpu<Bus> {
+    let mut bus = Bus::default();
+    bus.unmapped_set(unmapped);
+    bus.connect("low", 0x0000, 0x01FF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    bus.connect("top", 0xFF00, 0xFFFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu
+}
+
+#[test]
+fn test_bus_unmapped_open_bus() {
+    let mut cpu = cpu(
+        r#"
+        ;;
+    LDA     $2000
+    STA     $3000
+    NOP
+        "#,
+        Unmapped::OpenBus,
+    );
+    cpu.tick_until_nop().expect("Running program failed");
+    // The last byte on the bus was the high byte of the address
+    assert_eq!(cpu.a_get(), 0x20);
+}
+
+#[test]
+fn test_bus_unmapped_value() {
+    let mut cpu = cpu(
+        r#"
+        ;;
+    LDA     $2000
+    NOP
+        "#,
+        Unmapped::Value(0xFF),
+    );
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.a_get(), 0xFF);
+}
+
+#[test]
+fn test_bus_unmapped_fault() {
+    let mut cpu = cpu(
+        r#"
+        ;;
+    LDA     $2000
+    NOP
+        "#,
+        Unmapped::Fault,
+    );
+    let err = cpu.tick_until_nop().expect_err("LDA should fail");
+    assert_eq!(
+        err,
+        CpuError::Bus {
+            pc: 0x0000,
+            opcode: Some(0xAD),
+            source: BusError::OutOfBounds(0x2000),
+        }
+    );
+}
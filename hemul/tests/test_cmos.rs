@@ -0,0 +1,377 @@
+// Testing of the WDC 65C02 variant, written with .byte as the assembler does not know the CMOS
+// opcodes
+
+use hemul::{
+    Interruptible, Resettable, RunState, Snapshottable, Tickable,
+    cpu::{Cpu, CpuError, IllegalOpCodes, Variant, snapshot::Snapshot},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+fn cpu(variant: Variant, program: &str) -> Cpu<Memory> {
+    let mut cpu = Cpu::with_variant(Memory::from(program), variant);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu
+}
+
+fn run(variant: Variant, program: &str) -> Snapshot {
+    let mut cpu = cpu(variant, program);
+    cpu.tick_until_nop().expect("Running program failed");
+    cpu.snapshot().expect("Failed to create snapshot")
+}
+
+#[test]
+fn test_cmos_stack() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    LDX     #$42
+    LDY     #$43
+    .byte   $DA         ; PHX
+    .byte   $5A         ; PHY
+    .byte   $FA         ; PLX
+    .byte   $7A         ; PLY
+    NOP
+        "#,
+    );
+    assert_eq!(snapshot.X, 0x43);
+    assert_eq!(snapshot.Y, 0x42);
+}
+
+#[test]
+fn test_cmos_accumulator() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    LDA     #$FF
+    .byte   $1A         ; INC A
+    STA     $20
+    .byte   $3A         ; DEC A
+    .byte   $3A         ; DEC A
+    NOP
+        "#,
+    );
+    assert_eq!(snapshot.dump[0x20], 0x00);
+    assert_eq!(snapshot.A, 0xFE);
+    assert!(snapshot.N);
+}
+
+#[test]
+fn test_cmos_store_and_test_bits() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $64, $10    ; STZ $10
+    LDA     #$0F
+    .byte   $04, $11    ; TSB $11
+    .byte   $14, $12    ; TRB $12
+    NOP
+    .org    $0010
+    .byte   $FF, $F0, $FF
+        "#,
+    );
+    assert_eq!(snapshot.dump[0x10], 0x00);
+    assert_eq!(snapshot.dump[0x11], 0xFF);
+    assert_eq!(snapshot.dump[0x12], 0xF0);
+    assert!(!snapshot.Z);
+}
+
+#[test]
+fn test_cmos_zero_page_indirect() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $B2, $10    ; LDA ($10)
+    .byte   $92, $12    ; STA ($12)
+    NOP
+    .org    $0010
+    .word   $2000
+    .word   $2001
+    .org    $2000
+    .byte   $42
+        "#,
+    );
+    assert_eq!(snapshot.A, 0x42);
+    assert_eq!(snapshot.dump[0x2001], 0x42);
+}
+
+#[test]
+fn test_cmos_zero_page_indirect_wraps() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $B2, $FF    ; LDA ($FF)
+    NOP
+    .org    $00FF
+    .byte   $00
+    .byte   $20
+    .org    $2000
+    .byte   $01
+    .org    $B200
+    .byte   $42
+        "#,
+    );
+
+    // The high byte of the pointer comes from $00, which holds the op code
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_cmos_jump_indexed_indirect() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    LDX     #$02
+    .byte   $7C, $00, $10   ; JMP ($1000,X)
+    .org    $1000
+    .word   $3000
+    .word   $2000
+    .org    $2000
+    LDY     #$42
+    NOP
+        "#,
+    );
+    assert_eq!(snapshot.Y, 0x42);
+}
+
+#[test]
+fn test_cmos_jump_indirect_page() {
+    let program = r#"
+        ;;
+    JMP     ($10FF)
+    .org    $1000
+    .byte   $20
+    .org    $10FF
+    .word   $3000
+    .org    $2000
+    LDY     #$01
+    NOP
+    .org    $3000
+    LDY     #$02
+    NOP
+        "#;
+
+    // The NMOS 6502 reads the high byte from the start of the same page
+    assert_eq!(run(Variant::Nmos6502, program).Y, 0x01);
+    assert_eq!(run(Variant::Wdc65C02, program).Y, 0x02);
+}
+
+#[test]
+fn test_cmos_jump_indirect_wraps() {
+    let program = |op: &str| {
+        format!(
+            r#"
+        ;;
+    .byte   {op}, $FF, $FF
+    .org    $6C00
+    LDY     #$01
+    NOP
+    .org    $7C00
+    LDY     #$02
+    NOP
+    .org    $FFFF
+    .byte   $00
+        "#
+        )
+    };
+
+    // The high byte of the target comes from $0000, which holds the op code
+    assert_eq!(run(Variant::Wdc65C02, &program("$6C")).Y, 0x01); // JMP ($FFFF)
+    assert_eq!(run(Variant::Wdc65C02, &program("$7C")).Y, 0x02); // JMP ($FFFF,X)
+}
+
+#[test]
+fn test_cmos_branches() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $80, $02        ; BRA +2
+    LDX     #$01
+    .byte   $0F, $10, $02   ; BBR0 $10,+2
+    LDY     #$01
+    .byte   $9F, $10, $02   ; BBS1 $10,+2
+    LDA     #$01
+    NOP
+    .org    $0010
+    .byte   $02
+        "#,
+    );
+    assert_eq!(snapshot.X, 0x00);
+    assert_eq!(snapshot.Y, 0x00);
+    assert_eq!(snapshot.A, 0x00);
+}
+
+#[test]
+fn test_cmos_memory_bits() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $07, $10    ; RMB0 $10
+    .byte   $F7, $11    ; SMB7 $11
+    NOP
+    .org    $0010
+    .byte   $FF, $00
+        "#,
+    );
+    assert_eq!(snapshot.dump[0x10], 0xFE);
+    assert_eq!(snapshot.dump[0x11], 0x80);
+}
+
+#[test]
+fn test_cmos_bit_immediate() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    LDA     #$0F
+    .byte   $89, $F0    ; BIT #$F0
+    NOP
+        "#,
+    );
+    assert!(snapshot.Z);
+    assert!(!snapshot.N);
+    assert!(!snapshot.V);
+}
+
+#[test]
+fn test_cmos_decimal_flags() {
+    let program = r#"
+        ;;
+    SED
+    CLC
+    LDA     #$99
+    ADC     #$01
+    NOP
+        "#;
+
+    // $99 + $01 = $00, but the NMOS 6502 sets Z from the binary result $9A
+    let snapshot = run(Variant::Nmos6502, program);
+    assert_eq!(snapshot.A, 0x00);
+    assert!(!snapshot.Z);
+    assert!(snapshot.N);
+
+    let snapshot = run(Variant::Wdc65C02, program);
+    assert_eq!(snapshot.A, 0x00);
+    assert!(snapshot.Z);
+    assert!(!snapshot.N);
+    assert!(snapshot.C);
+}
+
+#[test]
+fn test_cmos_interrupt_clears_decimal() {
+    let program = r#"
+        ;;
+    SED
+    BRK
+    .org    $8000
+    NOP
+    .org    $FFFE
+    .word   $8000
+        "#;
+    assert!(run(Variant::Nmos6502, program).D);
+    assert!(!run(Variant::Wdc65C02, program).D);
+}
+
+#[test]
+fn test_cmos_wait() {
+    let mut cpu = cpu(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    SEI
+    .byte   $CB         ; WAI
+    LDX     #$42
+    NOP
+    .org    $8000
+    LDY     #$43
+    NOP
+    .org    $FFFA
+    .word   $8000
+        "#,
+    );
+    cpu.tick_for(10).expect("Waiting failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x0002);
+    assert_eq!(snapshot.X, 0x00);
+    assert_eq!(cpu.run_state_get(), RunState::Waiting);
+
+    // A masked IRQ wakes the processor up without being taken
+    let mut irq = cpu.irq();
+    irq.assert();
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.X, 0x42);
+    assert_eq!(snapshot.Y, 0x00);
+    assert_eq!(cpu.run_state_get(), RunState::Running);
+}
+
+#[test]
+fn test_cmos_stop() {
+    let mut cpu = cpu(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $DB         ; STP
+    NOP
+        "#,
+    );
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.run_state_get(), RunState::Stopped);
+
+    // Interrupts do not get it going again
+    let mut nmi = cpu.nmi();
+    nmi.assert();
+    cpu.tick_for(10).expect("Running program failed");
+    assert_eq!(cpu.run_state_get(), RunState::Stopped);
+    assert_eq!(
+        cpu.snapshot().expect("Failed to create snapshot").PC,
+        0x0000
+    );
+}
+
+#[test]
+fn test_cmos_undefined_nops() {
+    let snapshot = run(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $03             ; NOP
+    .byte   $02, $A9        ; NOP #$A9
+    .byte   $44, $A9        ; NOP $A9
+    .byte   $DC, $A9, $01   ; NOP $01A9
+    LDA     #$42
+    NOP
+        "#,
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_cmos_undefined_nops_as_error() {
+    let mut cpu = cpu(
+        Variant::Wdc65C02,
+        r#"
+        ;;
+    .byte   $03         ; NOP
+    NOP
+        "#,
+    );
+    cpu.illegal_opcodes_set(IllegalOpCodes::Error);
+    let err = cpu.tick_for(2).expect_err("Reserved NOP should fail");
+    assert_eq!(
+        err,
+        CpuError::BadOpCode {
+            pc: 0x0000,
+            opcode: 0x03
+        }
+    );
+}
@@ -0,0 +1,160 @@
+// Testing of cycle counts, including page crossing and branch penalties
+
+use hemul::{
+    Resettable, Snapshottable,
+    cpu::{Cpu, Mode},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+#[path = "utils.rs"]
+mod utils;
+
+/// Run a program and return the cycles it used up to the first NOP, after the 7 cycle reset
+fn cycles(program: &str) -> u64 {
+    let mut cpu = hemul::asm!(program);
+    cpu.tick_until_nop().expect("Running program failed");
+    cpu.cycles_get() - 7
+}
+
+#[test]
+fn test_cycles_page_crossing() {
+    for (program, expected) in [
+        // LDX (2) + LDA abs,X (4)
+        ("    LDX #$01\n    LDA $10FE,X\n    NOP", 6),
+        // LDX (2) + LDA abs,X (4+1)
+        ("    LDX #$01\n    LDA $10FF,X\n    NOP", 7),
+        // LDY (2) + LDA abs,Y (4+1)
+        ("    LDY #$FF\n    LDA $1001,Y\n    NOP", 7),
+        // LDX (2) + STA abs,X (5), stores always take the extra cycle
+        ("    LDX #$01\n    STA $10FF,X\n    NOP", 7),
+        // LDX (2) + INC abs,X (7)
+        ("    LDX #$01\n    INC $10FF,X\n    NOP", 9),
+        // LDY (2) + LDA (zp),Y (5+1)
+        (
+            "    LDY #$01\n    LDA ($10),Y\n    NOP\n    .org $10\n    .word $10FF",
+            8,
+        ),
+        // LDY (2) + LDA (zp),Y (5)
+        (
+            "    LDY #$01\n    LDA ($10),Y\n    NOP\n    .org $10\n    .word $1000",
+            7,
+        ),
+    ] {
+        assert_eq!(cycles(program), expected, "{program}");
+    }
+}
+
+#[test]
+fn test_cycles_branch() {
+    for (program, expected) in [
+        // CLC (2) + BCS not taken (2)
+        ("    CLC\n    BCS end\n    NOP\nend:\n    NOP", 4),
+        // SEC (2) + BCS taken (2+1)
+        ("    SEC\n    BCS end\n    NOP\nend:\n    NOP", 5),
+        // JMP (3) + SEC (2) + BCS taken to a new page (2+2)
+        (
+            "    JMP $10F0\n    .org $10F0\n    SEC\n    BCS end\n    .org $1100\nend:\n    NOP",
+            9,
+        ),
+    ] {
+        assert_eq!(cycles(program), expected, "{program}");
+    }
+}
+
+#[test]
+fn test_cycles_backward_branch() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDX     #$03
+loop:
+    DEX
+    BNE     loop
+    NOP
+        "#
+    );
+    assert_eq!(snapshot.X, 0x00);
+
+    // LDX (2) + 3 * DEX (2) + 2 * BNE taken (3) + BNE not taken (2)
+    assert_eq!(
+        cycles("    LDX #$03\nloop:\n    DEX\n    BNE loop\n    NOP"),
+        2 + 3 * 2 + 2 * 3 + 2
+    );
+}
+
+#[test]
+fn test_cycles_stepped() {
+    let program = r#"
+        ;;
+    LDX     #$05
+loop:
+    LDA     $10FF,X
+    ADC     #$01
+    STA     $2000,X
+    INC     $2000,X
+    JSR     sub
+    DEX
+    BNE     loop
+    NOP
+sub:
+    PHA
+    PLA
+    RTS
+    .org    $1100
+    .byte   $10, $20, $30, $40, $50
+        "#;
+
+    let mut fast = hemul::asm!(program);
+    fast.tick_until_nop().expect("Running program failed");
+
+    let mut stepped = hemul::asm!(program);
+    stepped.mode_set(Mode::Stepped);
+    stepped.tick_until_nop().expect("Running program failed");
+
+    let (fast_snapshot, stepped_snapshot) = (
+        fast.snapshot().expect("Failed to create snapshot"),
+        stepped.snapshot().expect("Failed to create snapshot"),
+    );
+    assert_eq!(stepped.cycles_get(), fast.cycles_get());
+    assert_eq!(stepped_snapshot.PC, fast_snapshot.PC);
+    assert_eq!(stepped_snapshot.A, fast_snapshot.A);
+    assert_eq!(stepped_snapshot.dump, fast_snapshot.dump);
+    assert_eq!(
+        stepped_snapshot.dump[0x2001..0x2006],
+        [0x12, 0x22, 0x32, 0x42, 0x52]
+    );
+}
+
+#[test]
+fn test_cycles_counters() {
+    let program = r#"
+        ;;
+    LDX     #$03
+loop:
+    DEX
+    BNE     loop
+    NOP
+    LDY     #$01
+    NOP
+        "#;
+    let mut cpu = Cpu::with_mode(Memory::from(program), Mode::Fast);
+    cpu.reset().expect("Resetting CPU failed");
+    assert_eq!(cpu.mode_get(), Mode::Fast);
+
+    // Reset (7) + LDX (2) + 3 * DEX (2) + 2 * BNE taken (3) + BNE not taken (2)
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.instructions_get(), 1 + 3 + 3);
+    assert_eq!(cpu.cycles_get(), 7 + 2 + 3 * 2 + 2 * 3 + 2);
+
+    // Switching mode keeps counting, NOP (2) + LDY (2)
+    cpu.mode_set(Mode::Original);
+    cpu.tick_for(2 + 2).expect("Running program failed");
+    assert_eq!(cpu.instructions_get(), 1 + 3 + 3 + 2);
+    assert_eq!(cpu.cycles_get(), 7 + 2 + 3 * 2 + 2 * 3 + 2 + 2 + 2);
+
+    cpu.reset().expect("Resetting CPU failed");
+    assert_eq!(cpu.instructions_get(), 0);
+    assert_eq!(cpu.cycles_get(), 0);
+}
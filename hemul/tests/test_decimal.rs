@@ -0,0 +1,119 @@
+// Testing of decimal mode arithmetic, see http://www.6502.org/tutorials/decimal_mode.html
+
+use hemul::{Byte, Snapshottable, cpu::snapshot::Snapshot};
+
+extern crate hemul;
+
+const ADC_IMMEDIATE: Byte = 0x69;
+const SBC_IMMEDIATE: Byte = 0xE9;
+
+/// Run `SED; CLC/SEC; LDA #a; <op> #b; NOP` and snapshot the result
+fn run(op: Byte, a: Byte, b: Byte, carry: bool) -> Snapshot {
+    let program = [0xF8, if carry { 0x38 } else { 0x18 }, 0xA9, a, op, b, 0xEA];
+    let mut cpu = hemul::asm!(&program[..]);
+    cpu.tick_until_nop().expect("Running program failed");
+    cpu.snapshot().expect("Failed to create snapshot")
+}
+
+fn bcd(n: u8) -> Byte {
+    ((n / 10) << 4) | (n % 10)
+}
+
+/// Results of the NMOS 6502 from the examples of the tutorial, including invalid BCD operands:
+/// (A, operand, C) gives (A, C, Z, N, V)
+type Vector = ((Byte, Byte, bool), (Byte, bool, bool, bool, bool));
+
+const ADC_VECTORS: [Vector; 10] = [
+    ((0x00, 0x00, false), (0x00, false, true, false, false)),
+    ((0x79, 0x00, true), (0x80, false, false, true, true)),
+    ((0x24, 0x56, false), (0x80, false, false, true, true)),
+    ((0x93, 0x82, false), (0x75, true, false, false, true)),
+    ((0x89, 0x76, false), (0x65, true, false, false, false)),
+    ((0x89, 0x76, true), (0x66, true, true, false, false)),
+    ((0x80, 0xF0, false), (0xD0, true, false, false, true)),
+    ((0x80, 0xFA, false), (0xE0, true, false, true, false)),
+    ((0x2F, 0x4F, false), (0x74, false, false, false, false)),
+    ((0x6F, 0x00, true), (0x76, false, false, false, false)),
+];
+
+const SBC_VECTORS: [Vector; 7] = [
+    ((0x00, 0x00, false), (0x99, false, false, true, false)),
+    ((0x00, 0x00, true), (0x00, true, true, false, false)),
+    ((0x00, 0x01, true), (0x99, false, false, true, false)),
+    ((0x0A, 0x00, true), (0x0A, true, false, false, false)),
+    ((0x0B, 0x00, false), (0x0A, true, false, false, false)),
+    ((0x9A, 0x00, true), (0x9A, true, false, true, false)),
+    ((0x9B, 0x00, false), (0x9A, true, false, true, false)),
+];
+
+#[test]
+fn test_decimal_adc_example() {
+    // $99 + $01 = $00 with carry, but Z follows the binary result $9A
+    let snapshot = run(ADC_IMMEDIATE, 0x99, 0x01, false);
+    assert_eq!(snapshot.A, 0x00);
+    assert!(snapshot.C);
+    assert!(!snapshot.Z);
+    assert!(snapshot.D);
+}
+
+#[test]
+fn test_decimal_sbc_example() {
+    // $00 - $01 = $99 with borrow
+    let snapshot = run(SBC_IMMEDIATE, 0x00, 0x01, true);
+    assert_eq!(snapshot.A, 0x99);
+    assert!(!snapshot.C);
+}
+
+#[test]
+fn test_decimal_valid_bcd() {
+    for a in 0..100u8 {
+        for b in 0..100u8 {
+            for c in [false, true] {
+                // The NMOS 6502 sets Z from the binary sum
+                let binary = u16::from(bcd(a)) + u16::from(bcd(b)) + u16::from(c);
+                let sum = u16::from(a) + u16::from(b) + u16::from(c);
+                let snapshot = run(ADC_IMMEDIATE, bcd(a), bcd(b), c);
+                assert_eq!(snapshot.A, bcd((sum % 100) as u8), "{a} + {b} + {c}");
+                assert_eq!(snapshot.C, sum >= 100, "{a} + {b} + {c}");
+                assert_eq!(snapshot.Z, binary & 0xFF == 0, "{a} + {b} + {c}");
+
+                // SBC sets every flag like in binary mode
+                let binary = i16::from(bcd(a)) - i16::from(bcd(b)) - i16::from(!c);
+                let signed = i16::from(bcd(a).cast_signed())
+                    - i16::from(bcd(b).cast_signed())
+                    - i16::from(!c);
+                let diff = i16::from(a) - i16::from(b) - i16::from(!c);
+                let snapshot = run(SBC_IMMEDIATE, bcd(a), bcd(b), c);
+                assert_eq!(
+                    (snapshot.A, snapshot.C, snapshot.Z, snapshot.N, snapshot.V),
+                    (
+                        bcd(diff.rem_euclid(100) as u8),
+                        diff >= 0,
+                        binary & 0xFF == 0,
+                        binary & 0x80 > 0,
+                        !(-128..=127).contains(&signed),
+                    ),
+                    "{a} - {b} - {}",
+                    !c
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_decimal_vectors() {
+    for (op, vectors) in [
+        (ADC_IMMEDIATE, &ADC_VECTORS[..]),
+        (SBC_IMMEDIATE, &SBC_VECTORS),
+    ] {
+        for &((a, b, c), expected) in vectors {
+            let snapshot = run(op, a, b, c);
+            assert_eq!(
+                (snapshot.A, snapshot.C, snapshot.Z, snapshot.N, snapshot.V),
+                expected,
+                "{op:#04x} with {a:#04x}, {b:#04x} and C={c}"
+            );
+        }
+    }
+}
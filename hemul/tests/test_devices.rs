@@ -0,0 +1,118 @@
+// Testing of devices on the bus that have side effects when read
+
+use hemul::{
+    Addressable, Byte, Device, Resettable, Snapshottable, Word, bus::Bus, cpu::Cpu, memory::Memory,
+};
+
+extern crate hemul;
+
+/// Receive full bit of the status register
+const RX_FULL: Byte = 0b0000_1000;
+
+/// Serial port with a data register at $8000 and a status register at $8001, reading the data
+/// clears the receive full bit
+struct Acia {
+    data: Byte,
+    status: Byte,
+}
+
+impl Device for Acia {
+    fn inside_bounds(&self, _addr: Word) -> bool {
+        true
+    }
+
+    fn peek(&self, addr: Word) -> Byte {
+        if addr & 1 == 0 {
+            self.data
+        } else {
+            self.status
+        }
+    }
+
+    fn read(&mut self, addr: Word) -> Byte {
+        if addr & 1 == 0 {
+            self.status &= !RX_FULL;
+        }
+        self.peek(addr)
+    }
+
+    fn write(&mut self, addr: Word, data: Byte) {
+        if addr & 1 == 0 {
+            self.data = data;
+        }
+    }
+}
+
+fn cpu(program: &str) -> Cpu<Bus> {
+    let mut bus = Bus::default();
+    bus.connect("ram", 0x0000, 0x7FFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    bus.connect(
+        "acia",
+        0x8000,
+        0x8001,
+        Box::new(Acia {
+            data: 0x42,
+            status: RX_FULL,
+        }),
+    )
+    .expect("Connecting device failed");
+    bus.connect("rom", 0xFF00, 0xFFFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu
+}
+
+#[test]
+fn test_device_read_side_effect() {
+    let mut cpu = cpu(r#"
+        ;;
+    LDX     $8001
+    LDA     $8000
+    LDY     $8001
+    NOP
+        "#);
+
+    // Snapshots do not read like the Cpu does
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.dump[0x8001], RX_FULL);
+
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.x_get(), RX_FULL);
+    assert_eq!(cpu.a_get(), 0x42);
+    assert_eq!(cpu.y_get(), 0x00);
+}
+
+#[test]
+fn test_device_write() {
+    let mut cpu = cpu(r#"
+        ;;
+    LDA     #$37
+    STA     $8000
+    LDX     $8000
+    NOP
+        "#);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.x_get(), 0x37);
+}
+
+#[test]
+fn test_device_addressable() {
+    let mut memory = Memory::from(
+        r#"
+        ;;
+    LDA     $8000
+    NOP
+        "#,
+    );
+    memory[0x8000] = 0x99;
+    let boxed: Box<dyn Addressable> = Box::new(memory);
+    let mut bus = Bus::default();
+    bus.connect_addressable("ram", 0x0000, 0xFFFF, boxed)
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.a_get(), 0x99);
+}
@@ -0,0 +1,99 @@
+// Testing of the errors the Cpu fails with
+
+use hemul::{
+    Error, Resettable, Tickable,
+    bus::{Bus, BusError},
+    cpu::{Cpu, CpuError},
+    memory::Memory,
+    oscillator::Oscillator,
+};
+
+extern crate hemul;
+
+/// Cpu with the program mapped at $0000-$01FF and $FF00-$FFFF, and nothing in between
+fn cpu(program: &str) -> Cpu<Bus> {
+    let mut bus = Bus::default();
+    bus.connect("low", 0x0000, 0x01FF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    bus.connect("top", 0xFF00, 0xFFFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu
+}
+
+#[test]
+fn test_error_bus_fault() {
+    let mut cpu = cpu(r#"
+        ;;
+    LDX     #$01
+    LDA     $2000
+    NOP
+        "#);
+    let err = cpu.tick_until_nop().expect_err("LDA should fail");
+    assert_eq!(
+        err,
+        CpuError::Bus {
+            pc: 0x0002,
+            opcode: Some(0xAD),
+            source: BusError::OutOfBounds(0x2000),
+        }
+    );
+}
+
+#[test]
+fn test_error_bus_fault_in_reset() {
+    let mut bus = Bus::default();
+    bus.connect("zero", 0x0000, 0x00FF, Box::new(Memory::default()))
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    let err = cpu.tick_for(1).expect_err("Reset should fail");
+    assert!(matches!(
+        err,
+        CpuError::Bus {
+            opcode: None,
+            source: BusError::OutOfBounds(0x0100),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_error_endless_loop() {
+    let mut cpu = cpu(r#"
+        ;;
+    JMP     $0000
+        "#);
+    let err = cpu.tick_until_nop().expect_err("Loop should not end");
+    assert!(matches!(err, CpuError::EndlessLoop(_)));
+}
+
+#[test]
+fn test_error_device_name() {
+    let mut cpu = cpu(r#"
+        ;;
+    STA     $2000
+        "#);
+    cpu.tick().expect("Reset failed");
+
+    let mut oscillator = Oscillator::from_hertz(1_000_000_000);
+    oscillator.connect("cpu", Box::new(cpu));
+    let err = loop {
+        if let Err(e) = oscillator.tick() {
+            break e;
+        }
+    };
+    let Error::Device { name, source } = err else {
+        panic!("Expected a device error, got {err}");
+    };
+    assert_eq!(name, "cpu");
+    assert!(matches!(
+        *source,
+        Error::Cpu(CpuError::Bus {
+            pc: 0x0000,
+            opcode: Some(0x8D),
+            source: BusError::OutOfBounds(0x2000),
+        })
+    ));
+}
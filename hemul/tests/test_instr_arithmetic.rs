@@ -109,6 +109,37 @@ proptest! {
         assert_eq!(snapshot.C, !carry);
     }
 
+    #[test]
+    fn test_instr_arithmetic_overflow(a in 0..255u8, b in 0..255u8, sub in proptest::bool::ANY) {
+        let snapshot = asm_test!(
+            format!(
+            r#"
+            ;;
+    SEC
+    LDA     $2000
+    {}      $2002
+    BVS     overflow
+    LDX     #$02
+    NOP
+overflow:
+    LDX     #$01
+    NOP
+    .org    $2000
+    .word   ${}
+    .word   ${}
+            "#,
+            if sub { "SBC" } else { "ADC" },
+            as_hex(a),
+            as_hex(b)
+            )
+        );
+        let (a, b) = (i16::from(a.cast_signed()), i16::from(b.cast_signed()));
+        let res = if sub { a - b } else { a + b + 1 };
+        let overflow = !(-128..=127).contains(&res);
+        assert_eq!(snapshot.V, overflow);
+        assert_eq!(snapshot.X, if overflow { 0x01 } else { 0x02 });
+    }
+
     #[test]
     fn test_instr_arithmetic_compare(reg in registers(), a in 0..255u8, b in 0..255u8) {
         let instr = match reg {
@@ -10,23 +10,23 @@ mod utils;
 
 pub fn cases() -> impl Strategy<Value = (&'static str, u8, bool)> {
     prop_oneof![
-        //    OP     S: CZI DBVN, Branch?
-        Just(("BCC", 0b1011_0111, true)),
-        Just(("BCC", 0b0100_0000, false)),
-        Just(("BCS", 0b1011_0111, false)),
-        Just(("BCS", 0b0100_0000, true)),
-        Just(("BEQ", 0b1101_0111, false)),
-        Just(("BEQ", 0b0010_0000, true)),
-        Just(("BMI", 0b1111_0110, false)),
-        Just(("BMI", 0b0000_0001, true)),
-        Just(("BNE", 0b1101_0111, true)),
-        Just(("BNE", 0b0010_0000, false)),
-        Just(("BPL", 0b1111_0110, true)),
-        Just(("BPL", 0b0000_0001, false)),
-        Just(("BVC", 0b1111_0101, true)),
-        Just(("BVC", 0b0000_0010, false)),
-        Just(("BVS", 0b1111_0101, false)),
-        Just(("BVS", 0b0000_0010, true)),
+        //    OP     S: NV-B DIZC, Branch?
+        Just(("BCC", 0b1111_0110, true)),
+        Just(("BCC", 0b0010_0001, false)),
+        Just(("BCS", 0b1111_0110, false)),
+        Just(("BCS", 0b0010_0001, true)),
+        Just(("BEQ", 0b1111_0101, false)),
+        Just(("BEQ", 0b0010_0010, true)),
+        Just(("BMI", 0b0111_0111, false)),
+        Just(("BMI", 0b1010_0000, true)),
+        Just(("BNE", 0b1111_0101, true)),
+        Just(("BNE", 0b0010_0010, false)),
+        Just(("BPL", 0b0111_0111, true)),
+        Just(("BPL", 0b1010_0000, false)),
+        Just(("BVC", 0b1011_0111, true)),
+        Just(("BVC", 0b0110_0000, false)),
+        Just(("BVS", 0b1011_0111, false)),
+        Just(("BVS", 0b0110_0000, true)),
     ]
 }
 
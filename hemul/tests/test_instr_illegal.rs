@@ -0,0 +1,239 @@
+// Testing of undocumented opcodes, written with .byte as the assembler does not know them
+
+use hemul::{
+    Resettable, RunState, Snapshottable, Tickable,
+    cpu::{CpuError, IllegalOpCodes},
+};
+
+extern crate hemul;
+
+#[path = "utils.rs"]
+mod utils;
+
+#[test]
+fn test_instr_illegal_lax() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    .byte   $A7, $10    ; LAX $10
+    NOP
+    .org    $0010
+    .byte   $87
+        "#
+    );
+    assert_eq!(snapshot.A, 0x87);
+    assert_eq!(snapshot.X, 0x87);
+    assert!(snapshot.N);
+}
+
+#[test]
+fn test_instr_illegal_sax() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     #$F0
+    LDX     #$3C
+    .byte   $87, $20    ; SAX $20
+    NOP
+        "#
+    );
+    assert_eq!(snapshot.dump[0x20], 0x30);
+}
+
+#[test]
+fn test_instr_illegal_dcp() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     #$42
+    .byte   $C7, $10    ; DCP $10
+    NOP
+    .org    $0010
+    .byte   $43
+        "#
+    );
+    assert_eq!(snapshot.dump[0x10], 0x42);
+    assert!(snapshot.Z);
+    assert!(snapshot.C);
+}
+
+#[test]
+fn test_instr_illegal_isc() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    SEC
+    LDA     #$10
+    .byte   $E7, $10    ; ISC $10
+    NOP
+    .org    $0010
+    .byte   $0F
+        "#
+    );
+    assert_eq!(snapshot.dump[0x10], 0x10);
+    assert_eq!(snapshot.A, 0x00);
+    assert!(snapshot.Z);
+    assert!(snapshot.C);
+}
+
+#[test]
+fn test_instr_illegal_slo() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     #$02
+    .byte   $07, $10    ; SLO $10
+    NOP
+    .org    $0010
+    .byte   $81
+        "#
+    );
+    assert_eq!(snapshot.dump[0x10], 0x02);
+    assert_eq!(snapshot.A, 0x02);
+    assert!(snapshot.C);
+}
+
+#[test]
+fn test_instr_illegal_rla() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    SEC
+    LDA     #$FF
+    .byte   $27, $10    ; RLA $10
+    NOP
+    .org    $0010
+    .byte   $40
+        "#
+    );
+    assert_eq!(snapshot.dump[0x10], 0x81);
+    assert_eq!(snapshot.A, 0x81);
+    assert!(!snapshot.C);
+    assert!(snapshot.N);
+}
+
+#[test]
+fn test_instr_illegal_sre() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     #$F0
+    .byte   $47, $10    ; SRE $10
+    NOP
+    .org    $0010
+    .byte   $03
+        "#
+    );
+    assert_eq!(snapshot.dump[0x10], 0x01);
+    assert_eq!(snapshot.A, 0xF1);
+    assert!(snapshot.C);
+}
+
+#[test]
+fn test_instr_illegal_rra() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    CLC
+    LDA     #$10
+    .byte   $67, $10    ; RRA $10
+    NOP
+    .org    $0010
+    .byte   $03
+        "#
+    );
+    // $03 rotates to $01 with carry, then $10 + $01 + 1
+    assert_eq!(snapshot.dump[0x10], 0x01);
+    assert_eq!(snapshot.A, 0x12);
+    assert!(!snapshot.C);
+}
+
+#[test]
+fn test_instr_illegal_immediate() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     #$F0
+    .byte   $0B, $80    ; ANC #$80
+    STA     $20
+    LDA     #$FF
+    .byte   $4B, $03    ; ALR #$03
+    STA     $21
+    SEC
+    LDA     #$FF
+    .byte   $6B, $FF    ; ARR #$FF
+    STA     $22
+    LDA     #$F0
+    LDX     #$3F
+    .byte   $CB, $10    ; SBX #$10
+    NOP
+        "#
+    );
+    assert_eq!(snapshot.dump[0x20], 0x80);
+    assert_eq!(snapshot.dump[0x21], 0x01);
+    assert_eq!(snapshot.dump[0x22], 0xFF);
+    assert_eq!(snapshot.X, 0x20);
+    assert!(snapshot.C);
+}
+
+#[test]
+fn test_instr_illegal_nops() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    .byte   $1A             ; NOP
+    .byte   $80, $A9        ; NOP #$A9
+    .byte   $04, $A9        ; NOP $A9
+    .byte   $0C, $A9, $01   ; NOP $01A9
+    .byte   $FC, $A9, $01   ; NOP $01A9,X
+    LDA     #$42
+    NOP
+        "#
+    );
+    assert_eq!(snapshot.A, 0x42);
+}
+
+#[test]
+fn test_instr_illegal_jam() {
+    let mut cpu = hemul::asm!(&[0x02, 0xEA][..]);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.run_state_get(), RunState::Jammed(0x02));
+
+    // The processor stays on the JAM until reset
+    cpu.tick_for(10).expect("Running program failed");
+    assert_eq!(cpu.run_state_get(), RunState::Jammed(0x02));
+    assert_eq!(
+        cpu.snapshot().expect("Failed to create snapshot").PC,
+        0x0000
+    );
+
+    cpu.reset().expect("Resetting CPU failed");
+    cpu.tick().expect("Running program failed");
+    assert_eq!(cpu.run_state_get(), RunState::Running);
+}
+
+#[test]
+fn test_instr_illegal_as_nop() {
+    // LAX #$42 (LXA), LAX $10
+    let mut cpu = hemul::asm!(&[0xAB, 0x42, 0xA7, 0x10, 0xEA][..]);
+    cpu.illegal_opcodes_set(IllegalOpCodes::Nop);
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x0004);
+    assert_eq!(snapshot.A, 0x00);
+    assert_eq!(snapshot.X, 0x00);
+}
+
+#[test]
+fn test_instr_illegal_as_error() {
+    let mut cpu = hemul::asm!(&[0xA7, 0x10, 0xEA][..]);
+    cpu.illegal_opcodes_set(IllegalOpCodes::Error);
+    let err = cpu.tick_until_nop().expect_err("LAX should fail");
+    assert_eq!(
+        err,
+        CpuError::BadOpCode {
+            pc: 0x0000,
+            opcode: 0xA7
+        }
+    );
+}
@@ -11,11 +11,12 @@ fn test_instr_interrupt() {
         r#"
         ;;
     BRK
+    .byte   $FF
     LDX     #$42
     NOP
     .org    $8000
     LDY     #$43
-    RTS
+    RTI
     .org    $FFFE
     .word   $8000
         "#
@@ -23,3 +24,47 @@ fn test_instr_interrupt() {
     assert_eq!(snapshot.X, 0x42);
     assert_eq!(snapshot.Y, 0x43);
 }
+
+#[test]
+fn test_instr_interrupt_stack_frame() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    SEC
+    BRK
+    .byte   $FF
+    .org    $8000
+    NOP
+    .org    $FFFE
+    .word   $8000
+        "#
+    );
+    // Return address skips the signature byte after BRK
+    assert_eq!(snapshot.dump[0x01FD], 0x00);
+    assert_eq!(snapshot.dump[0x01FC], 0x03);
+    //                                   NV-BDIZC
+    assert_eq!(snapshot.dump[0x01FB], 0b0011_0101);
+    assert_eq!(snapshot.SP, 0xFA);
+}
+
+#[test]
+fn test_instr_interrupt_return() {
+    let snapshot = asm_test!(
+        r#"
+        ;;
+    LDA     #$03
+    PHA
+    LDA     #$00
+    PHA
+    LDA     #$FF
+    PHA
+    RTI
+    .org    $0300
+    NOP
+        "#
+    );
+    // RTI restores the exact address and ignores the break and unused bits
+    assert_eq!(snapshot.PC, 0x0300);
+    assert!(snapshot.N);
+    assert!(snapshot.C);
+}
@@ -59,7 +59,7 @@ fn test_instr_trans_s_to_x() {
     NOP
         "#
     );
-    assert_eq!(snapshot.X, 0xFF);
+    assert_eq!(snapshot.X, 0xFD);
 }
 
 #[test]
@@ -85,7 +85,7 @@ fn test_instr_stack_push_a() {
     NOP
         "#
     );
-    assert_eq!(snapshot.dump[0x01FF], 0xDE);
+    assert_eq!(snapshot.dump[0x01FD], 0xDE);
 }
 
 #[test]
@@ -98,8 +98,8 @@ fn test_instr_stack_push_s() {
     NOP
         "#
     );
-    //                                   CZIDBVN
-    assert_eq!(snapshot.dump[0x01FF], 0b00000001);
+    //                                   NV-BDIZC
+    assert_eq!(snapshot.dump[0x01FD], 0b1011_0100);
 }
 
 #[test]
@@ -109,7 +109,7 @@ fn test_instr_stack_pull_a() {
         ;;
     PLA
     NOP
-    .org    $0100
+    .org    $01FE
     .word   $1234
         "#
     );
@@ -123,7 +123,7 @@ fn test_instr_stack_pull_s() {
         ;;
     PLP
     NOP
-    .org    $0100
+    .org    $01FE
     .word   $F7
         "#
     );
@@ -131,7 +131,6 @@ fn test_instr_stack_pull_s() {
     assert!(snapshot.Z);
     assert!(snapshot.I);
     assert!(!snapshot.D);
-    assert!(snapshot.B);
     assert!(snapshot.V);
     assert!(snapshot.N);
 }
@@ -0,0 +1,259 @@
+// Testing of the IRQ and NMI lines and when the processor polls them
+
+use hemul::{
+    Interruptible, Resettable, Snapshottable, Tickable,
+    cpu::{Cpu, Mode},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+const HANDLERS: &str = r#"
+    .org    $8000
+    LDY     #$43
+    NOP
+    RTI
+    .org    $9000
+    INY
+    NOP
+    RTI
+    .org    $FFFA
+    .word   $9000
+    .word   $0000
+    .word   $8000
+"#;
+
+fn setup(program: &str, mode: Mode) -> Cpu<Memory> {
+    let mut cpu = Cpu::new(Memory::from(format!("{program}{HANDLERS}")));
+    cpu.mode_set(mode);
+    cpu.reset().expect("Resetting CPU failed");
+    while cpu.cycles_get() < 7 {
+        cpu.tick().expect("Resetting CPU failed");
+    }
+    cpu
+}
+
+#[test]
+fn test_interrupts_irq_sequence() {
+    let mut cpu = setup(
+        r#"
+        ;;
+    CLI
+    LDX     #$42
+    NOP
+        "#,
+        Mode::Fast,
+    );
+    cpu.tick().expect("Running program failed");
+    let mut irq = cpu.irq();
+    irq.assert();
+
+    // LDX (2) + interrupt sequence (7)
+    let start = cpu.cycles_get();
+    cpu.tick_for(2).expect("Running program failed");
+    assert_eq!(cpu.cycles_get() - start, 9);
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x8000);
+    assert!(snapshot.I);
+
+    // The return address is the next instruction and the break flag is clear
+    assert_eq!(snapshot.dump[0x01FC], 0x03);
+    //                                   NV-BDIZC
+    assert_eq!(snapshot.dump[0x01FB], 0b0010_0000);
+
+    // LDY, NOP and RTI
+    irq.release();
+    cpu.tick_for(3).expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x0003);
+    assert_eq!(snapshot.Y, 0x43);
+    assert!(!snapshot.I);
+}
+
+#[test]
+fn test_interrupts_irq_masked() {
+    let mut cpu = setup(
+        r#"
+        ;;
+    SEI
+    LDX     #$42
+    NOP
+        "#,
+        Mode::Fast,
+    );
+    cpu.tick().expect("Running program failed");
+    let mut irq = cpu.irq();
+    irq.assert();
+
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.X, 0x42);
+    assert_eq!(snapshot.Y, 0x00);
+}
+
+#[test]
+fn test_interrupts_irq_flag_delay() {
+    // CLI and SEI poll with the flag as it was before them, so the IRQ comes in after the SEI
+    let mut cpu = setup(
+        r#"
+        ;;
+    SEI
+    CLI
+    SEI
+    LDX     #$42
+    NOP
+        "#,
+        Mode::Fast,
+    );
+    let mut irq = cpu.irq();
+    irq.assert();
+
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.X, 0x00);
+    assert_eq!(snapshot.Y, 0x43);
+}
+
+#[test]
+fn test_interrupts_irq_latency() {
+    let program = r#"
+        ;;
+    CLI
+    LDA     #$01
+    LDX     #$42
+    NOP
+        "#;
+
+    // Asserted at the start of the last cycle of LDX, the IRQ is taken right after it
+    let mut cpu = setup(program, Mode::Original);
+    cpu.tick_for(5).expect("Running program failed");
+    let mut irq = cpu.irq();
+    irq.assert();
+    cpu.tick_for(2).expect("Running program failed");
+    assert_eq!(
+        cpu.snapshot().expect("Failed to create snapshot").PC,
+        0x8000
+    );
+
+    // Asserted any later, it waits for the next instruction
+    let mut cpu = setup(program, Mode::Original);
+    cpu.tick_for(6).expect("Running program failed");
+    let mut irq = cpu.irq();
+    irq.assert();
+    cpu.tick_for(1).expect("Running program failed");
+    assert_eq!(
+        cpu.snapshot().expect("Failed to create snapshot").PC,
+        0x0006
+    );
+}
+
+#[test]
+fn test_interrupts_irq_branch() {
+    let program = r#"
+        ;;
+    CLI
+    SEC
+    BCS     next
+next:
+    LDX     #$42
+    NOP
+        "#;
+
+    // Asserted on the second cycle of a taken branch, the IRQ is taken after the branch
+    let mut cpu = setup(program, Mode::Original);
+    cpu.tick_for(5).expect("Running program failed");
+    let mut irq = cpu.irq();
+    irq.assert();
+    cpu.tick_for(3).expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x8000);
+    assert_eq!(snapshot.X, 0x00);
+
+    // A taken branch that stays on the page does not poll on its last cycle, so the IRQ is taken
+    // after the next instruction
+    let mut cpu = setup(program, Mode::Original);
+    cpu.tick_for(6).expect("Running program failed");
+    let mut irq = cpu.irq();
+    irq.assert();
+    cpu.tick_for(3).expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x0006);
+    assert_eq!(snapshot.X, 0x42);
+    cpu.tick().expect("Running program failed");
+    assert_eq!(
+        cpu.snapshot().expect("Failed to create snapshot").PC,
+        0x8000
+    );
+}
+
+#[test]
+fn test_interrupts_nmi_edge() {
+    let mut cpu = setup(
+        r#"
+        ;;
+    SEI
+loop:
+    JMP     loop
+        "#,
+        Mode::Fast,
+    );
+
+    // Holding the line only gives one NMI, even with interrupts disabled
+    let mut nmi = cpu.nmi();
+    nmi.assert();
+    cpu.tick_for(20).expect("Running program failed");
+    assert_eq!(cpu.snapshot().expect("Failed to create snapshot").Y, 1);
+
+    nmi.release();
+    cpu.tick().expect("Running program failed");
+    nmi.assert();
+    cpu.tick_for(20).expect("Running program failed");
+    assert_eq!(cpu.snapshot().expect("Failed to create snapshot").Y, 2);
+}
+
+#[test]
+fn test_interrupts_nmi_hijack() {
+    let mut cpu = setup(
+        r#"
+        ;;
+    BRK
+        "#,
+        Mode::Stepped,
+    );
+
+    // An NMI that comes in while BRK pushes to the stack takes over its vector
+    cpu.tick_for(3).expect("Running program failed");
+    let mut nmi = cpu.nmi();
+    nmi.assert();
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x9001);
+    assert_eq!(snapshot.Y, 0x01);
+
+    // Too late, and it is taken after the BRK instead
+    let mut cpu = setup(
+        r#"
+        ;;
+    BRK
+        "#,
+        Mode::Stepped,
+    );
+    cpu.tick_for(5).expect("Running program failed");
+    let mut nmi = cpu.nmi();
+    nmi.assert();
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x9001);
+    assert_eq!(snapshot.Y, 0x01);
+
+    // NOP (2) and RTI (6), then back in the IRQ handler
+    cpu.tick_for(2 + 6).expect("Running program failed");
+    assert_eq!(
+        cpu.snapshot().expect("Failed to create snapshot").PC,
+        0x8000
+    );
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x8002);
+    assert_eq!(snapshot.Y, 0x43);
+}
@@ -0,0 +1,113 @@
+// Testing of the tracing events, which need the `tracing` feature
+
+use std::sync::{Arc, Mutex};
+
+use hemul::Word;
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{Layer, layer::Context, prelude::*};
+
+extern crate hemul;
+
+/// An event that was emitted, with its pc field
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Logged {
+    target: String,
+    level: tracing::Level,
+    pc: Option<u64>,
+}
+
+/// Layer that keeps every event
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Logged>>>);
+
+impl Capture {
+    fn events(&self) -> Vec<Logged> {
+        self.0.lock().expect("Capture poisoned").clone()
+    }
+}
+
+struct Pc(Option<u64>);
+
+impl Visit for Pc {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "pc" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl<S: Subscriber> Layer<S> for Capture {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut pc = Pc(None);
+        event.record(&mut pc);
+        self.0.lock().expect("Capture poisoned").push(Logged {
+            target: event.metadata().target().to_string(),
+            level: *event.metadata().level(),
+            pc: pc.0,
+        });
+    }
+}
+
+/// Run the program until it reaches a NOP, logging the instructions in the range
+fn capture(program: &str, range: std::ops::RangeInclusive<Word>) -> Vec<Logged> {
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        let mut cpu = hemul::asm!(program);
+        cpu.log_range_set(range);
+        cpu.tick_until_nop().expect("Running program failed");
+    });
+    capture.events()
+}
+
+#[test]
+fn test_log_instructions() {
+    let events = capture(
+        r#"
+        ;;
+    LDA     #$01
+    LDX     #$02
+    LDY     #$03
+    NOP
+        "#,
+        0..=Word::MAX,
+    );
+
+    // The reset sequence, then every instruction
+    assert_eq!(events[0].level, tracing::Level::DEBUG);
+    let instructions: Vec<_> = events
+        .iter()
+        .filter(|e| e.level == tracing::Level::TRACE)
+        .collect();
+    assert!(instructions.iter().all(|e| e.target == "hemul::cpu"));
+    assert_eq!(
+        instructions.iter().map(|e| e.pc).collect::<Vec<_>>(),
+        vec![Some(0x0000), Some(0x0002), Some(0x0004)]
+    );
+}
+
+#[test]
+fn test_log_range() {
+    let events = capture(
+        r#"
+        ;;
+    LDA     #$01
+    LDX     #$02
+    LDY     #$03
+    NOP
+        "#,
+        0x0002..=0x0003,
+    );
+
+    let instructions: Vec<_> = events
+        .iter()
+        .filter(|e| e.level == tracing::Level::TRACE)
+        .map(|e| e.pc)
+        .collect();
+    assert_eq!(instructions, vec![Some(0x0002)]);
+}
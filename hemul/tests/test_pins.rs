@@ -0,0 +1,123 @@
+// Testing of the RDY, SO and SYNC pins
+
+use hemul::{
+    Resettable, Snapshottable, Tickable,
+    cpu::{Cpu, Mode, Variant},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+fn setup(variant: Variant, program: &str, mode: Mode) -> Cpu<Memory> {
+    let mut cpu = Cpu::with_variant_and_mode(Memory::from(program), variant, mode);
+    cpu.reset().expect("Resetting CPU failed");
+    while cpu.cycles_get() < 7 {
+        cpu.tick().expect("Resetting CPU failed");
+    }
+    cpu
+}
+
+#[test]
+fn test_pins_rdy_stalls_reads() {
+    let program = r#"
+        ;;
+    LDA     #$42
+    STA     $20
+    NOP
+        "#;
+
+    for (variant, stored) in [(Variant::Nmos6502, 0x42), (Variant::Wdc65C02, 0x00)] {
+        let mut cpu = setup(variant, program, Mode::Stepped);
+
+        // LDA, then the op code and operand of STA
+        cpu.tick_for(2 + 2).expect("Running program failed");
+        let mut rdy = cpu.rdy();
+        rdy.assert();
+
+        // Only the 65C02 stalls on the write
+        cpu.tick_for(3).expect("Running program failed");
+        let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+        assert_eq!(snapshot.dump[0x20], stored, "{variant:?}");
+        assert_eq!(cpu.cycles_get(), 7 + 4 + 3);
+
+        rdy.release();
+        cpu.tick_until_nop().expect("Running program failed");
+        let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+        assert_eq!(snapshot.dump[0x20], 0x42, "{variant:?}");
+        assert_eq!(snapshot.PC, 0x0004, "{variant:?}");
+    }
+}
+
+#[test]
+fn test_pins_rdy_stalls_fetch() {
+    let mut cpu = setup(
+        Variant::Nmos6502,
+        r#"
+        ;;
+    LDX     #$42
+    NOP
+        "#,
+        Mode::Fast,
+    );
+    let mut rdy = cpu.rdy();
+    rdy.assert();
+
+    // SYNC stays high while the op code fetch is held
+    cpu.tick_for(5).expect("Running program failed");
+    assert!(cpu.sync_get());
+    assert_eq!(cpu.cycles_get(), 7 + 5);
+    assert_eq!(cpu.snapshot().expect("Failed to create snapshot").X, 0x00);
+
+    drop(rdy);
+    cpu.tick_until_nop().expect("Running program failed");
+    assert_eq!(cpu.snapshot().expect("Failed to create snapshot").X, 0x42);
+    assert_eq!(cpu.cycles_get(), 7 + 5 + 2);
+}
+
+#[test]
+fn test_pins_so_sets_overflow() {
+    let mut cpu = setup(
+        Variant::Nmos6502,
+        r#"
+        ;;
+    CLV
+loop:
+    BVC     loop
+    LDX     #$42
+    CLV
+    NOP
+        "#,
+        Mode::Fast,
+    );
+    cpu.tick_for(10).expect("Running program failed");
+    assert_eq!(cpu.snapshot().expect("Failed to create snapshot").X, 0x00);
+
+    // Only the falling edge sets V, so CLV clears it for good while SO is held low
+    let mut so = cpu.so();
+    so.assert();
+    cpu.tick_until_nop().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.X, 0x42);
+    assert!(!snapshot.V);
+}
+
+#[test]
+fn test_pins_sync() {
+    let mut cpu = setup(
+        Variant::Nmos6502,
+        r#"
+        ;;
+    LDA     #$01
+    LDA     $1234
+    NOP
+        "#,
+        Mode::Original,
+    );
+
+    let mut sync = Vec::new();
+    for _ in 0..6 {
+        cpu.tick().expect("Running program failed");
+        sync.push(cpu.sync_get());
+    }
+    assert_eq!(sync, [true, false, true, false, false, false]);
+}
@@ -0,0 +1,44 @@
+// Testing of reading and setting registers and flags directly
+
+use hemul::{
+    Snapshottable, Tickable,
+    cpu::{Cpu, status::Status},
+    memory::Memory,
+};
+
+extern crate hemul;
+
+#[test]
+fn test_registers_poke() {
+    // ADC #$01, PHP, NOP
+    let mut cpu = Cpu::new(Memory::from(&[0x69, 0x01, 0x08, 0xEA][..]));
+
+    // Set up the registers without going through reset
+    let mut status = Status::default();
+    status.carry_set(true);
+    cpu.pc_set(0x0000);
+    cpu.sp_set(0xFF);
+    cpu.a_set(0x7F);
+    cpu.x_set(0x12);
+    cpu.y_set(0x34);
+    cpu.status_set(status);
+
+    cpu.tick_for(2).expect("Running program failed");
+    assert_eq!(cpu.pc_get(), 0x0003);
+    assert_eq!(cpu.sp_get(), 0xFE);
+    assert_eq!(cpu.a_get(), 0x81);
+    assert_eq!(cpu.x_get(), 0x12);
+    assert_eq!(cpu.y_get(), 0x34);
+
+    let status = cpu.status_get();
+    assert!(!status.carry_get());
+    assert!(!status.zero_get());
+    assert!(status.overflow_get());
+    assert!(status.negative_get());
+
+    // PHP pushes the status with the break and unused bits set
+    cpu.tick().expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.dump[0x01FF], status.pushed(true));
+    assert_eq!(snapshot.dump[0x01FF], 0b1111_0000);
+}
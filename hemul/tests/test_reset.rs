@@ -0,0 +1,83 @@
+// Testing of the reset sequence and the power-on state
+
+use hemul::{
+    Resettable, Snapshottable, Tickable,
+    cpu::{Cpu, Mode},
+    memory::Memory,
+    power::PowerOn,
+};
+
+extern crate hemul;
+
+const PROGRAM: &str = r#"
+        ;;
+    BRK
+    .org    $1000
+    LDX     #$42
+    NOP
+    .org    $FFFC
+    .word   $1000
+"#;
+
+#[test]
+fn test_reset_sequence() {
+    let mut cpu = Cpu::new(Memory::from(PROGRAM));
+    cpu.mode_set(Mode::Original);
+    cpu.reset().expect("Resetting CPU failed");
+
+    // The reset sequence takes 7 cycles before the first instruction runs
+    cpu.tick_for(7).expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x1000);
+    assert_eq!(snapshot.SP, 0xFD);
+    assert_eq!(snapshot.X, 0x00);
+    assert!(snapshot.I);
+
+    cpu.tick_for(2).expect("Running program failed");
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x1002);
+    assert_eq!(snapshot.X, 0x42);
+    assert_eq!(cpu.cycles_get(), 9);
+}
+
+#[test]
+fn test_reset_keeps_registers() {
+    let mut cpu = Cpu::new(Memory::from(PROGRAM));
+    cpu.power_on(PowerOn::Pattern(0xA5));
+    cpu.reset().expect("Resetting CPU failed");
+    cpu.tick().expect("Running program failed");
+
+    // Reset only moves SP and PC, the stack writes are suppressed
+    let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+    assert_eq!(snapshot.PC, 0x1000);
+    assert_eq!(snapshot.SP, 0xA2);
+    assert_eq!(snapshot.A, 0xA5);
+    assert_eq!(snapshot.X, 0xA5);
+    assert_eq!(snapshot.Y, 0xA5);
+    assert_eq!(snapshot.dump[0x01A3..=0x01A5], [0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn test_reset_power_on_random() {
+    let power_on = PowerOn::Random(6502);
+    let registers = || {
+        let mut cpu = Cpu::new(Memory::from(PROGRAM));
+        cpu.power_on(power_on);
+        let snapshot = cpu.snapshot().expect("Failed to create snapshot");
+        (snapshot.PC, snapshot.SP, snapshot.A, snapshot.X, snapshot.Y)
+    };
+    assert_eq!(registers(), registers());
+
+    // Loading a program leaves the rest of RAM as it powered on
+    let mut memory = Memory::with_power_on(power_on);
+    memory
+        .load(&[0xA2, 0x42, 0xEA])
+        .expect("Loading memory failed");
+    let dump = memory.snapshot().expect("Failed to create snapshot");
+    let fresh = Memory::with_power_on(power_on)
+        .snapshot()
+        .expect("Failed to create snapshot");
+    assert_eq!(dump[..3], [0xA2, 0x42, 0xEA]);
+    assert_eq!(dump[3..], fresh[3..]);
+    assert!(dump[3..].iter().any(|b| *b != 0));
+}
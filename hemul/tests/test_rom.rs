@@ -0,0 +1,103 @@
+// Testing of ROM on the bus
+
+use hemul::{
+    Device, Resettable,
+    bus::{Bus, BusError},
+    cpu::{Cpu, CpuError},
+    memory::Memory,
+    rom::{Rom, RomError, WritePolicy},
+};
+
+extern crate hemul;
+
+/// Cpu with the program in RAM at $0000-$7FFF and ROM at $8000-$FFFF, starting with $12 and
+/// filled with zeros so the reset vector points to $0000
+fn cpu(program: &str, policy: WritePolicy) -> Cpu<Bus> {
+    let mut rom = Rom::padded(&[0x12], 0x8000, 0x00).expect("Loading ROM failed");
+    rom.write_policy_set(policy);
+
+    let mut bus = Bus::default();
+    bus.connect("ram", 0x0000, 0x7FFF, Box::new(Memory::from(program)))
+        .expect("Connecting device failed");
+    bus.connect("rom", 0x8000, 0xFFFF, Box::new(rom))
+        .expect("Connecting device failed");
+    let mut cpu = Cpu::new(bus);
+    cpu.reset().expect("Resetting CPU failed");
+    cpu
+}
+
+#[test]
+fn test_rom_write_ignored() {
+    for policy in [WritePolicy::Ignore, WritePolicy::Log] {
+        let mut cpu = cpu(
+            r#"
+        ;;
+    LDA     #$37
+    STA     $8000
+    LDX     $8000
+    NOP
+        "#,
+            policy,
+        );
+        cpu.tick_until_nop().expect("Running program failed");
+        assert_eq!(cpu.x_get(), 0x12);
+    }
+}
+
+#[test]
+fn test_rom_write_error() {
+    let mut cpu = cpu(
+        r#"
+        ;;
+    LDA     #$37
+    STA     $8000
+    NOP
+        "#,
+        WritePolicy::Error,
+    );
+    let err = cpu.tick_until_nop().expect_err("STA should fail");
+    assert_eq!(
+        err,
+        CpuError::Bus {
+            pc: 0x0002,
+            opcode: Some(0x8D),
+            source: BusError::ReadOnly(0x8000),
+        }
+    );
+}
+
+#[test]
+fn test_rom_padded() {
+    let rom = Rom::padded(&[1, 2], 4, 0xFF).expect("Loading ROM failed");
+    assert_eq!(rom.peek(1), 2);
+    assert_eq!(rom.peek(3), 0xFF);
+    assert!(rom.inside_bounds(3));
+    assert!(!rom.inside_bounds(4));
+
+    assert!(matches!(
+        Rom::padded(&[0; 5], 4, 0xFF),
+        Err(RomError::TooLarge(5, 4))
+    ));
+}
+
+#[test]
+fn test_rom_open() {
+    let path = std::env::temp_dir().join(format!("hemul-test-rom-{}.bin", std::process::id()));
+    std::fs::write(&path, [0xA9, 0x42]).expect("Writing image failed");
+
+    let rom = Rom::open(&path).expect("Loading ROM failed");
+    assert_eq!(rom.peek(1), 0x42);
+    assert!(!rom.inside_bounds(2));
+
+    let rom = Rom::open_padded(&path, 0x100, 0xEA).expect("Loading ROM failed");
+    assert_eq!(rom.peek(0), 0xA9);
+    assert_eq!(rom.peek(0xFF), 0xEA);
+
+    assert!(matches!(
+        Rom::open_padded(&path, 1, 0xEA),
+        Err(RomError::TooLarge(2, 1))
+    ));
+    std::fs::remove_file(&path).expect("Removing image failed");
+
+    assert!(matches!(Rom::open(&path), Err(RomError::Io(_))));
+}
@@ -0,0 +1,111 @@
+// Testing of stepping through instructions and what they report
+
+use hemul::{
+    Resettable,
+    cpu::{
+        AddressMode, Cpu, Mode, OpCode, RESB,
+        record::{Access, Record},
+    },
+    memory::Memory,
+};
+
+extern crate hemul;
+
+const PROGRAM: &str = r#"
+        ;;
+    LDX     #$01
+    LDA     $10FF,X
+    STA     $20
+    .byte   $02     ; JAM
+    .org    $1100
+    .byte   $42
+        "#;
+
+fn read(addr: u16, data: u8) -> Access {
+    Access {
+        addr,
+        data,
+        write: false,
+    }
+}
+
+fn steps(mode: Mode) -> Vec<Option<Record>> {
+    let mut cpu = Cpu::with_mode(Memory::from(PROGRAM), mode);
+    cpu.reset().expect("Resetting CPU failed");
+    (0..6)
+        .map(|_| cpu.step().expect("Stepping failed"))
+        .collect()
+}
+
+#[test]
+fn test_step_records() {
+    let records = steps(Mode::Fast);
+
+    // The reset sequence runs like a BRK
+    let reset = records[0].as_ref().expect("Reset did not run");
+    assert_eq!(reset.op, OpCode::Brk);
+    assert_eq!(reset.interrupt, Some(RESB));
+    assert_eq!(reset.cycles, 7);
+
+    assert_eq!(
+        records[1],
+        Some(Record {
+            pc: 0x0000,
+            opcode: 0xA2,
+            op: OpCode::Ldx,
+            mode: AddressMode::Immediate,
+            operands: vec![0x01],
+            addr: Some(0x0001),
+            interrupt: None,
+            cycles: 2,
+            accesses: vec![read(0x0000, 0xA2), read(0x0001, 0x01)],
+        })
+    );
+
+    // Indexing across a page reads the address without the carry first
+    assert_eq!(
+        records[2],
+        Some(Record {
+            pc: 0x0002,
+            opcode: 0xBD,
+            op: OpCode::Lda,
+            mode: AddressMode::AbsoluteX,
+            operands: vec![0xFF, 0x10],
+            addr: Some(0x1100),
+            interrupt: None,
+            cycles: 5,
+            accesses: vec![
+                read(0x0002, 0xBD),
+                read(0x0003, 0xFF),
+                read(0x0004, 0x10),
+                read(0x1000, 0x00),
+                read(0x1100, 0x42),
+            ],
+        })
+    );
+
+    let store = records[3].as_ref().expect("STA did not run");
+    assert_eq!(store.addr, Some(0x0020));
+    assert_eq!(
+        store.accesses.last(),
+        Some(&Access {
+            addr: 0x0020,
+            data: 0x42,
+            write: true,
+        })
+    );
+
+    // Nothing runs once the processor is jammed
+    assert_eq!(
+        records[4].as_ref().map(|record| record.op),
+        Some(OpCode::Jam)
+    );
+    assert_eq!(records[5], None);
+}
+
+#[test]
+fn test_step_modes() {
+    let fast = steps(Mode::Fast);
+    assert_eq!(steps(Mode::Original), fast);
+    assert_eq!(steps(Mode::Stepped), fast);
+}
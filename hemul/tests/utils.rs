@@ -30,7 +30,7 @@ macro_rules! asm_test {
         let mut cpu = hemul::asm!($a);
         cpu.reset().expect("Resetting CPU failed");
         let res = cpu.tick_until_nop();
-        let snapshot = dbg!(cpu.snapshot());
+        let snapshot = cpu.snapshot();
         assert!(res.is_ok());
         if let Err(ref e) = snapshot {
             assert!(false, "Failed to create snapshot: {}", e);